@@ -0,0 +1,223 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    coalesceable::Coalesceable,
+    entailment::entails,
+    expression::Expr,
+    parseable::{split_top_level, Parseable},
+    Map,
+};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Query {
+    Prove(Expr),
+    Equiv(Expr, Expr),
+    Entails(Vec<Expr>, Expr),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Proven,
+    Unproven,
+    // The premises used, by index
+    Entailed(Vec<usize>),
+    // An assignment on which the two sides differ
+    Counterexample(Map<String, bool>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Report {
+    pub query: Query,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+// Lines are numbered from 1, as by editors
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Batch {
+    pub queries: Vec<(usize, Query)>,
+}
+
+impl std::fmt::Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Query::Prove(expr) => f.write_fmt(format_args!("prove {expr:?}")),
+            Query::Equiv(left, right) => f.write_fmt(format_args!("equiv {left:?}, {right:?}")),
+            Query::Entails(premises, goal) => f.write_fmt(format_args!(
+                "entails {} |- {goal:?}",
+                premises
+                    .iter()
+                    .map(|premise| format!("{premise:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Proven => f.write_str("proven"),
+            Outcome::Unproven => f.write_str("unproven"),
+            Outcome::Entailed(core) => f.write_fmt(format_args!("entailed by {core:?}")),
+            Outcome::Counterexample(assignment) => {
+                f.write_fmt(format_args!("differs on {assignment:?}"))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}: {} ({:?})",
+            self.query, self.outcome, self.elapsed
+        ))
+    }
+}
+
+impl Outcome {
+    pub fn holds(&self) -> bool {
+        matches!(self, Outcome::Proven | Outcome::Entailed(_))
+    }
+}
+
+impl Query {
+    pub fn run(&self) -> Report {
+        log::trace!("[run] {self}");
+        let start = Instant::now();
+        let outcome = match self {
+            Query::Prove(expr) => match expr.normal().coalesce() {
+                Some(_) => Outcome::Proven,
+                None => Outcome::Unproven,
+            },
+            Query::Equiv(left, right) => match left.equivalent(right) {
+                Ok(()) => Outcome::Proven,
+                Err(Some(assignment)) => Outcome::Counterexample(assignment),
+                Err(None) => Outcome::Unproven,
+            },
+            Query::Entails(premises, goal) => match entails(premises, goal) {
+                Some(entailment) => Outcome::Entailed(entailment.core),
+                None => Outcome::Unproven,
+            },
+        };
+        Report {
+            query: self.clone(),
+            outcome,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+// Statements end with a top-level ; and may span lines, and // comments to the
+// end of a line
+fn statements(input: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut statements = vec![];
+    let mut statement = String::new();
+    let mut start = 1;
+    let mut depth = 0;
+    for (index, line) in input.lines().enumerate() {
+        let line = line.split_once("//").map_or(line, |(line, _)| line);
+        for c in line.chars() {
+            match c {
+                ';' if depth == 0 => {
+                    statements.push((start, std::mem::take(&mut statement)));
+                    continue;
+                }
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                _ => (),
+            }
+            if statement.trim().is_empty() {
+                start = index + 1;
+            }
+            statement.push(c);
+        }
+        statement.push('\n');
+    }
+    match statement.trim() {
+        "" => Ok(statements),
+        statement => Err(format!("line {start}: Expected ; after {statement:?}")),
+    }
+}
+
+impl Batch {
+    fn statement(
+        &mut self,
+        input: &str,
+        line: usize,
+        definitions: &mut Map<String, Expr>,
+    ) -> Result<(), String> {
+        log::trace!("[statement] {input:?}");
+        let parse =
+            |input: &str| Expr::parse(input.trim()).map(|expr| expr.substitute(definitions));
+        let (keyword, rest) = input
+            .split_once(char::is_whitespace)
+            .ok_or(format!("Expected a statement, got {input:?}"))?;
+        let query = match keyword {
+            "let" => {
+                let (name, expr) = rest
+                    .split_once('=')
+                    .ok_or(format!("Expected let name = formula, got {input:?}"))?;
+                let name = name.trim();
+                if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(format!("Expected a name, got {name:?}"));
+                }
+                let expr = parse(expr)?;
+                definitions.insert(name.to_string(), expr);
+                return Ok(());
+            }
+            "prove" => Query::Prove(parse(rest)?),
+            "equiv" => match split_top_level(rest, ",").as_slice() {
+                [left, right] => Query::Equiv(parse(left)?, parse(right)?),
+                _ => return Err(format!("Expected equiv formula, formula, got {input:?}")),
+            },
+            "entails" => match split_top_level(rest, "|-").as_slice() {
+                [premises, goal] => Query::Entails(
+                    match premises.trim() {
+                        "" => vec![],
+                        premises => split_top_level(premises, ",")
+                            .into_iter()
+                            .map(parse)
+                            .collect::<Result<_, _>>()?,
+                    },
+                    parse(goal)?,
+                ),
+                _ => {
+                    return Err(format!(
+                        "Expected entails formula, .. |- formula, got {input:?}"
+                    ))
+                }
+            },
+            keyword => return Err(format!("Unsupported statement {keyword}")),
+        };
+        self.queries.push((line, query));
+        Ok(())
+    }
+
+    // Each definition is expanded where it is referenced, so must come first
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut batch = Batch::default();
+        let mut definitions = Map::new();
+        for (line, statement) in statements(input)? {
+            batch
+                .statement(statement.trim(), line, &mut definitions)
+                .map_err(|err| format!("line {line}: {err}"))?;
+        }
+        Ok(batch)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let input = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Batch::parse(&input).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    pub fn run(&self) -> Vec<Report> {
+        self.queries.iter().map(|(_, query)| query.run()).collect()
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use coalescence::{
+    cache::ProofCache,
+    expression::Expr,
+    parseable::{parse_compact, Parseable},
+    prolog::{parse_prolog, parse_prolog_formula},
+    sequent::Sequent,
+    texable::Texable,
+};
+
+use crate::output::{Outcome, Output};
+
+mod output;
+mod repl;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Propositional theorem proving by coalescence",
+    after_help = "Exits with 0 on success, 1 if any formula is not valid, and 2 on any error"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prove each formula, failing if any is not valid
+    Prove {
+        #[arg(long, value_enum, default_value_t = Output::Pretty)]
+        output: Output,
+        #[arg(long, value_enum, default_value_t = Syntax::Infix)]
+        syntax: Syntax,
+        /// Directory of proofs to reuse from and save to between runs
+        #[arg(long)]
+        cache: Option<PathBuf>,
+        /// Files of one formula per line, or stdin if none are given
+        files: Vec<PathBuf>,
+    },
+    /// Print the normal form of each formula
+    Normalize {
+        /// Files of one formula per line, or stdin if none are given
+        files: Vec<PathBuf>,
+    },
+    /// Parse each formula, printing it back unless only checking
+    Parse {
+        #[arg(long)]
+        check: bool,
+        /// Files of one formula per line, or stdin if none are given
+        files: Vec<PathBuf>,
+    },
+    /// Convert each formula from one syntax to another
+    Convert {
+        #[arg(long, value_enum, default_value_t = Syntax::Infix)]
+        from: Syntax,
+        #[arg(long, value_enum, default_value_t = Target::Infix)]
+        to: Target,
+        /// Files of one formula per line, or stdin if none are given
+        files: Vec<PathBuf>,
+    },
+    /// Interactively prove formulas, as in the Prolog sequent prover
+    Repl {
+        /// File to load and save line history
+        #[arg(long)]
+        history: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum Syntax {
+    Infix,
+    /// The C prover's syntax, as in c/1-dnf
+    Compact,
+    /// The Prolog sequent prover's syntax, a formula or a sequent
+    Prolog,
+    Json,
+}
+
+// The syntaxes formulas can be written in
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum Target {
+    Infix,
+    Json,
+    Tex,
+}
+
+struct Line {
+    source: String,
+    number: usize,
+    text: String,
+}
+
+fn read_lines(files: &[PathBuf]) -> Result<Vec<Line>, String> {
+    // Stdin can only be read through once
+    let stdins = files.iter().filter(|file| file.to_str() == Some("-"));
+    if stdins.count() > 1 {
+        return Err("- for stdin given more than once".to_string());
+    }
+    let readers: Vec<(String, Box<dyn BufRead>)> = match files {
+        [] => vec![("<stdin>".to_string(), Box::new(io::stdin().lock()))],
+        files => files
+            .iter()
+            .map(|file| match file.to_str() {
+                Some("-") => Ok((
+                    "<stdin>".to_string(),
+                    Box::new(io::stdin().lock()) as Box<_>,
+                )),
+                _ => File::open(file)
+                    .map(|handle| {
+                        (
+                            file.display().to_string(),
+                            Box::new(BufReader::new(handle)) as Box<_>,
+                        )
+                    })
+                    .map_err(|err| format!("{}: {err}", file.display())),
+            })
+            .collect::<Result<_, _>>()?,
+    };
+
+    readers
+        .into_iter()
+        .flat_map(|(source, reader)| {
+            reader.lines().enumerate().map(move |(index, text)| {
+                text.map(|text| Line {
+                    source: source.clone(),
+                    number: index + 1,
+                    text,
+                })
+                .map_err(|err| format!("{source}: {err}"))
+            })
+        })
+        .filter(|line| !matches!(line, Ok(line) if line.text.trim().is_empty()))
+        .collect()
+}
+
+fn read(syntax: Syntax, input: &str) -> Result<Expr, String> {
+    match syntax {
+        Syntax::Infix => Expr::parse(input),
+        Syntax::Compact => parse_compact(input),
+        Syntax::Prolog if input.contains("-->") => parse_prolog(input).map(|sequents| {
+            Expr::and(&sequents.iter().map(Sequent::expr).collect::<Vec<_>>()).normal()
+        }),
+        Syntax::Prolog => parse_prolog_formula(input),
+        Syntax::Json => serde_json::from_str(input).map_err(|err| err.to_string()),
+    }
+}
+
+fn write(target: Target, expr: &Expr) -> Result<String, String> {
+    match target {
+        Target::Infix => Ok(format!("{expr:?}")),
+        Target::Json => serde_json::to_string(expr).map_err(|err| err.to_string()),
+        Target::Tex => Ok(expr.tex()),
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    let files = match &cli.command {
+        Command::Prove { files, .. }
+        | Command::Normalize { files }
+        | Command::Parse { files, .. }
+        | Command::Convert { files, .. } => files,
+        Command::Repl { history } => {
+            return match repl::run(history.as_deref()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    ExitCode::from(2)
+                }
+            }
+        }
+    };
+    let lines = match read_lines(files) {
+        Ok(lines) => lines,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    let cache = match &cli.command {
+        Command::Prove {
+            cache: Some(cache), ..
+        } => match ProofCache::open(cache) {
+            Ok(cache) => Some(cache),
+            Err(err) => {
+                eprintln!("error: {err}");
+                return ExitCode::from(2);
+            }
+        },
+        _ => None,
+    };
+
+    let mut errors = 0;
+    let mut failures = 0;
+    for line in lines {
+        let result = match &cli.command {
+            Command::Prove { output, syntax, .. } => read(*syntax, &line.text)
+                .and_then(|expr| match &cache {
+                    Some(cache) => Outcome::prove_cached(&expr, cache),
+                    None => Ok(Outcome::prove(&expr, None)),
+                })
+                .map(|outcome| {
+                    print!("{}", outcome.render(*output));
+                    outcome.is_valid()
+                }),
+            Command::Normalize { .. } => Expr::parse(&line.text).map(|expr| {
+                println!("{:?}", expr.normal());
+                true
+            }),
+            Command::Parse { check, .. } => Expr::parse(&line.text).map(|expr| {
+                if !check {
+                    println!("{expr:?}");
+                }
+                true
+            }),
+            Command::Convert { from, to, .. } => read(*from, &line.text)
+                .and_then(|expr| write(*to, &expr))
+                .map(|converted| {
+                    println!("{converted}");
+                    true
+                }),
+            Command::Repl { .. } => unreachable!(),
+        };
+        match result {
+            Ok(true) => (),
+            Ok(false) => failures += 1,
+            Err(err) => {
+                eprintln!("error: {}:{}: {err}", line.source, line.number);
+                errors += 1;
+            }
+        }
+    }
+
+    match (errors, failures) {
+        (0, 0) => ExitCode::SUCCESS,
+        (0, _) => ExitCode::from(1),
+        _ => ExitCode::from(2),
+    }
+}
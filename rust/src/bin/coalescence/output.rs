@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use coalescence::{
+    cache::ProofCache,
+    coalesceable::Coalesceable,
+    expression::Expr,
+    proof::{Proof, ProofGraph},
+    texable::Texable,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Output {
+    Pretty,
+    Tex,
+    Json,
+    Dot,
+}
+
+impl std::fmt::Display for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .map(|value| f.write_str(value.get_name()))
+            .unwrap_or(Ok(()))
+    }
+}
+
+pub struct Outcome {
+    pub expr: Expr,
+    // A valid formula may still lack a proof, should reconstruction fail
+    pub valid: bool,
+    pub proof: Option<(Proof, ProofGraph)>,
+    pub elapsed: Duration,
+}
+
+impl Outcome {
+    pub fn prove(expr: &Expr, dim_bound: Option<usize>) -> Self {
+        let start = Instant::now();
+        let normal = expr.normal();
+        let result = match dim_bound {
+            Some(dim_bound) => normal.coalesce_within(dim_bound),
+            None => normal.coalesce(),
+        };
+        let proof = result.as_ref().and_then(|(_, dag)| {
+            let proof = Proof::reconstruct(&normal, dag);
+            if proof.is_none() {
+                log::warn!("Failed to reconstruct proof of {normal:?}");
+            }
+            proof.map(|proof| (proof.relabel(&expr.originals()), ProofGraph::from(dag)))
+        });
+
+        Outcome {
+            expr: expr.clone(),
+            valid: result.is_some(),
+            proof,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    pub fn prove_cached(expr: &Expr, cache: &ProofCache) -> Result<Self, String> {
+        let start = Instant::now();
+        // Cached proofs are of the normal form, whichever original was proven
+        let entry = cache.prove(expr)?;
+        Ok(Outcome {
+            expr: expr.clone(),
+            valid: entry.valid,
+            proof: entry
+                .proof
+                .map(|(proof, graph)| (proof.relabel(&expr.originals()), graph)),
+            elapsed: start.elapsed(),
+        })
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    pub fn render(&self, output: Output) -> String {
+        let expr = &self.expr;
+        let missing = "no proof could be reconstructed";
+        match (output, self.valid, &self.proof) {
+            (Output::Pretty, _, Some((proof, _))) => format!("valid: {expr:?}\n{proof}"),
+            (Output::Pretty, true, None) => format!("valid: {expr:?}\n  ({missing})\n"),
+            (Output::Pretty, false, _) => format!("invalid: {expr:?}\n"),
+            (Output::Tex, _, Some((proof, _))) => format!("{}\n", proof.tex()),
+            (Output::Tex, true, None) => format!("% valid: ${}$, {missing}\n", expr.tex()),
+            (Output::Tex, false, _) => format!("% invalid: ${}$\n", expr.tex()),
+            (Output::Dot, _, Some((_, graph))) => format!("{}\n", graph.dot()),
+            (Output::Dot, true, None) => format!("// valid: {expr:?}, {missing}\n"),
+            (Output::Dot, false, _) => format!("// invalid: {expr:?}\n"),
+            (Output::Json, valid, proof) => format!(
+                "{}\n",
+                serde_json::json!({
+                    "expr": expr,
+                    "valid": valid,
+                    "proof": proof.as_ref().map(|(_, graph)| graph),
+                })
+            ),
+        }
+    }
+}
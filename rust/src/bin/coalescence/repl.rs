@@ -0,0 +1,253 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use coalescence::{expression::Expr, parseable::Parseable, theory::Theory};
+use rustyline::{error::ReadlineError, Editor};
+
+use crate::output::{Outcome, Output};
+
+const PROMPT: &str = "ψ. ";
+
+const HELP: &str = "\
+Formula             : Try to prove the formula.
+help                : Help.
+init                : Initialize the prover.
+threshold[(N)]      : Set/retrieve the projection threshold. N>=0
+axioms[(A1,...,Am)] : Set/retrieve the axioms.
+output[(Form)]      : Set/retrieve the output form. Form={pretty|tex|json|dot}
+log[(L)]            : Start/stop/retrieve the output logging. L={File|no}
+N                   : Display the N-th output, or the last but -N if N<=0.
+quit                : Quit.
+";
+
+struct Session {
+    threshold: Option<usize>,
+    axioms: Theory,
+    output: Output,
+    log: Option<(PathBuf, File)>,
+    results: Vec<Outcome>,
+}
+
+fn split_arguments(input: &str) -> Vec<String> {
+    let mut depth = 0;
+    input
+        .split(|c| {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                _ => (),
+            }
+            c == ',' && depth == 0
+        })
+        .map(|argument| argument.trim().to_string())
+        .filter(|argument| !argument.is_empty())
+        .collect()
+}
+
+fn command(input: &str) -> Option<(&str, Option<&str>)> {
+    let name_len = input
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(input.len());
+    let (name, rest) = input.split_at(name_len);
+    match rest.trim() {
+        _ if name.is_empty() => None,
+        "" => Some((name, None)),
+        rest if rest.starts_with('(') && rest.ends_with(')') => {
+            Some((name, Some(rest[1..rest.len() - 1].trim())))
+        }
+        _ => None,
+    }
+}
+
+impl Session {
+    fn new() -> Self {
+        Session {
+            threshold: None,
+            axioms: Theory::new(),
+            output: Output::Pretty,
+            log: None,
+            results: vec![],
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        print!("{text}");
+        if let Some((path, file)) = &mut self.log {
+            if let Err(err) = file.write_all(text.as_bytes()) {
+                eprintln!("error: {}: {err}", path.display());
+            }
+        }
+    }
+
+    fn recall(&mut self, index: i64) -> Result<(), String> {
+        let count = self.results.len() as i64;
+        let position = if index > 0 { index } else { count + index };
+        let outcome = usize::try_from(position - 1)
+            .ok()
+            .and_then(|position| self.results.get(position))
+            .ok_or(format!("No output {index} of {count}"))?;
+        let text = format!(
+            "{}:{position} =\n{}",
+            self.output,
+            outcome.render(self.output)
+        );
+        self.write(&text);
+        Ok(())
+    }
+
+    fn prove(&mut self, input: &str) -> Result<(), String> {
+        let expr = Expr::parse(input)?;
+        let goal = match self.axioms.is_empty() {
+            true => expr.clone(),
+            false => self.axioms.sequent(&expr).expr(),
+        };
+        let outcome = Outcome {
+            expr,
+            ..Outcome::prove(&goal, self.threshold)
+        };
+        let used = match outcome.is_valid() && !self.axioms.is_empty() {
+            true => self
+                .axioms
+                .prove(&outcome.expr)
+                .map(|proof| format!(" using [{}]", proof.used.join(", ")))
+                .unwrap_or_default(),
+            false => String::new(),
+        };
+        let text = format!(
+            "{} {:?}{used} ({:?})\n",
+            match outcome.is_valid() {
+                true => "Succeeded in proving",
+                false => "Failed to prove",
+            },
+            outcome.expr,
+            outcome.elapsed
+        );
+        self.write(&text);
+        self.results.push(outcome);
+        self.recall(self.results.len() as i64)
+    }
+
+    fn execute(&mut self, input: &str) -> Result<bool, String> {
+        if let Ok(index) = input.parse::<i64>() {
+            self.recall(index)?;
+            return Ok(true);
+        }
+
+        match command(input) {
+            Some(("quit" | "exit" | "bye" | "halt", None)) => return Ok(false),
+            Some(("help", None)) => self.write(HELP),
+            Some(("init", None)) => *self = Session::new(),
+            Some(("threshold", None)) => {
+                let text = match self.threshold {
+                    Some(threshold) => format!("threshold({threshold})\n"),
+                    None => "threshold(default)\n".to_string(),
+                };
+                self.write(&text);
+            }
+            Some(("threshold", Some(threshold))) => {
+                self.threshold = match threshold {
+                    "default" => None,
+                    threshold => Some(
+                        threshold
+                            .parse()
+                            .map_err(|_| format!("Expected threshold N>=0, got {threshold:?}"))?,
+                    ),
+                }
+            }
+            Some(("axioms", None)) => {
+                let text = format!(
+                    "axioms({})\n",
+                    self.axioms
+                        .axioms
+                        .iter()
+                        .map(|(name, axiom)| format!("{name}: {axiom:?}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                self.write(&text);
+            }
+            Some(("axioms", Some(axioms))) => {
+                let axioms = axioms
+                    .strip_prefix('[')
+                    .and_then(|axioms| axioms.strip_suffix(']'))
+                    .unwrap_or(axioms);
+                self.axioms = split_arguments(axioms)
+                    .iter()
+                    .enumerate()
+                    .map(|(index, axiom)| Ok((format!("A{}", index + 1), Expr::parse(axiom)?)))
+                    .collect::<Result<_, String>>()?;
+            }
+            Some(("output", None)) => {
+                let text = format!("output({})\n", self.output);
+                self.write(&text);
+            }
+            Some(("output", Some(output))) => {
+                self.output = Output::from_str(output, true).map_err(|_| {
+                    format!("Expected output pretty, tex, json or dot, got {output:?}")
+                })?;
+            }
+            Some(("log", None)) => {
+                let text = match &self.log {
+                    Some((path, _)) => format!("log({})\n", path.display()),
+                    None => "log(no)\n".to_string(),
+                };
+                self.write(&text);
+            }
+            Some(("log", Some("no"))) => self.log = None,
+            Some(("log", Some(path))) => {
+                let path = PathBuf::from(path);
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .map_err(|err| format!("{}: {err}", path.display()))?;
+                self.log = Some((path, file));
+            }
+            _ => self.prove(input)?,
+        }
+        Ok(true)
+    }
+}
+
+pub fn run(history: Option<&Path>) -> Result<(), String> {
+    let mut editor = Editor::<()>::new().map_err(|err| err.to_string())?;
+    if let Some(history) = history {
+        // A missing history file is expected on first use
+        let _ = editor.load_history(history);
+    }
+
+    let mut session = Session::new();
+    loop {
+        match editor.readline(PROMPT) {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                editor.add_history_entry(line);
+                if let Some((path, file)) = &mut session.log {
+                    if let Err(err) = writeln!(file, "{PROMPT}{line}") {
+                        eprintln!("error: {}: {err}", path.display());
+                    }
+                }
+                match session.execute(line) {
+                    Ok(true) => (),
+                    Ok(false) => break,
+                    Err(err) => session.write(&format!("error: {err}\n")),
+                }
+            }
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+
+    match history {
+        Some(history) => editor.save_history(history).map_err(|err| err.to_string()),
+        None => Ok(()),
+    }
+}
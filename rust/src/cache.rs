@@ -0,0 +1,107 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    coalesceable::Coalesceable,
+    expression::Expr,
+    proof::{Proof, ProofGraph},
+};
+
+// Bump the suffix whenever coalescence or proof reconstruction changes, so
+// that entries written by an older engine are ignored
+pub const ENGINE_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "+3");
+
+// Distinguishes the partial entries written by each thread of this process
+static PARTIALS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CacheEntry {
+    pub version: String,
+    pub expr: Expr,
+    // A valid formula may still lack a proof, should reconstruction fail
+    pub valid: bool,
+    pub proof: Option<(Proof, ProofGraph)>,
+}
+
+// One json file per normalized formula, named by its digest
+#[derive(Clone, Debug)]
+pub struct ProofCache {
+    directory: PathBuf,
+}
+
+impl ProofCache {
+    pub fn open(directory: &Path) -> Result<Self, String> {
+        fs::create_dir_all(directory).map_err(|err| format!("{}: {err}", directory.display()))?;
+        Ok(ProofCache {
+            directory: directory.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self, expr: &Expr) -> PathBuf {
+        self.directory
+            .join(format!("{:016x}.json", expr.normal().digest()))
+    }
+
+    // None is a cache miss
+    pub fn get(&self, expr: &Expr) -> Option<CacheEntry> {
+        let normal = expr.normal();
+        let path = self.path(&normal);
+        let entry = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<CacheEntry>(&json).ok())?;
+        match entry {
+            CacheEntry { version, .. } if version != ENGINE_VERSION => {
+                log::debug!("[cache] {} is stale at {version}", path.display());
+                None
+            }
+            CacheEntry { expr, .. } if expr != normal => {
+                log::debug!("[cache] {} collides with {expr:?}", path.display());
+                None
+            }
+            entry => Some(entry),
+        }
+    }
+
+    pub fn insert(&self, entry: &CacheEntry) -> Result<(), String> {
+        let path = self.path(&entry.expr);
+        let json = serde_json::to_string(entry).map_err(|err| err.to_string())?;
+        // Write then rename, so concurrent readers never see a partial entry,
+        // and concurrent writers each rename their own
+        let partial = path.with_extension(format!(
+            "json.{}.{}.partial",
+            std::process::id(),
+            PARTIALS.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&partial, json)
+            .and_then(|()| fs::rename(&partial, &path))
+            .map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    pub fn prove(&self, expr: &Expr) -> Result<CacheEntry, String> {
+        log::trace!("[prove] {expr:?} cached in {}", self.directory.display());
+        if let Some(entry) = self.get(expr) {
+            return Ok(entry);
+        }
+
+        let normal = expr.normal();
+        let result = normal.coalesce();
+        let proof = result.as_ref().and_then(|(_, dag)| {
+            let proof = Proof::reconstruct(&normal, dag);
+            if proof.is_none() {
+                log::warn!("Failed to reconstruct proof of {normal:?}");
+            }
+            proof.map(|proof| (proof, ProofGraph::from(dag)))
+        });
+        let entry = CacheEntry {
+            version: ENGINE_VERSION.to_string(),
+            expr: normal,
+            valid: result.is_some(),
+            proof,
+        };
+        self.insert(&entry)?;
+        Ok(entry)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::expression::Expr;
+
+// How normal_with encodes cardinality constraints into And and Or
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Cardinality {
+    // No k + 1 operands are all true, so one clause per subset of k + 1
+    Pairwise,
+    // Sinz's sequential counter, with each register unrolled into a formula
+    // rather than a fresh atom, so that the encoding is equivalent. Unrolling
+    // shares nothing between registers, so it grows much as pairwise does
+    #[default]
+    Sequential,
+    // The sequential counter with each register a fresh atom, so linear in
+    // the operands times the count. The constraint holds whenever every
+    // register meets its definition, which preserves validity, as constraints
+    // are only encoded where they occur positively, but not equivalence.
+    // Registers are named _r..., which the parsers reject in user atoms
+    Registers,
+}
+
+fn subsets(exprs: &[Expr], size: usize) -> Vec<Vec<Expr>> {
+    match (exprs, size) {
+        (_, 0) => vec![vec![]],
+        ([], _) => vec![],
+        ([first, rest @ ..], size) => subsets(rest, size - 1)
+            .into_iter()
+            .map(|subset| [vec![first.clone()], subset].concat())
+            .chain(subsets(rest, size))
+            .collect(),
+    }
+}
+
+// Folds constants, so that registers out of reach stay small
+fn or(left: Expr, right: Expr) -> Expr {
+    match (left, right) {
+        (left, right) if left == Expr::top() || right == Expr::bottom() => left,
+        (left, right) if right == Expr::top() || left == Expr::bottom() => right,
+        (left, right) => Expr::or(&[left, right]),
+    }
+}
+
+fn and(left: Expr, right: Expr) -> Expr {
+    match (left, right) {
+        (left, right) if left == Expr::bottom() || right == Expr::top() => left,
+        (left, right) if right == Expr::bottom() || left == Expr::top() => right,
+        (left, right) => Expr::and(&[left, right]),
+    }
+}
+
+// The jth register holds whether at least j of the operands are true
+fn registers(exprs: &[Expr], count: usize) -> Vec<Expr> {
+    let initial = (0..=count)
+        .map(|j| match j {
+            0 => Expr::top(),
+            _ => Expr::bottom(),
+        })
+        .collect::<Vec<_>>();
+    exprs.iter().fold(initial, |registers, expr| {
+        (0..=count)
+            .map(|j| match j {
+                0 => Expr::top(),
+                _ => or(
+                    registers[j].clone(),
+                    and(expr.clone(), registers[j - 1].clone()),
+                ),
+            })
+            .collect()
+    })
+}
+
+// Registers depend only on the operands in order, so are named after them, and
+// constraints over the same operands share both registers and definitions
+fn definitions(exprs: &[Expr], count: usize) -> (Expr, Vec<Expr>) {
+    let prefix = format!("_r{:016x}", Expr::at_least(0, exprs).digest());
+    let register = |i: usize, j: usize| match j {
+        0 => Expr::top(),
+        j if j > i => Expr::bottom(),
+        j => Expr::Atom(format!("{prefix}_{i}_{j}")),
+    };
+    let definitions = exprs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, expr)| {
+            let i = index + 1;
+            let register = &register;
+            (1..=count.min(i)).map(move |j| {
+                Expr::iff(
+                    register(i, j),
+                    or(
+                        register(i - 1, j),
+                        and(expr.clone(), register(i - 1, j - 1)),
+                    ),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    let registers = (0..=count).map(|j| register(exprs.len(), j)).collect();
+    (Expr::and(&definitions), registers)
+}
+
+impl Cardinality {
+    pub fn at_least(&self, count: usize, exprs: &[Expr]) -> Expr {
+        match self {
+            _ if count == 0 => Expr::top(),
+            _ if count > exprs.len() => Expr::bottom(),
+            Cardinality::Pairwise => Expr::and(
+                &subsets(exprs, exprs.len() - count + 1)
+                    .iter()
+                    .map(|subset| Expr::or(subset))
+                    .collect::<Vec<_>>(),
+            ),
+            Cardinality::Sequential => registers(exprs, count).swap_remove(count),
+            Cardinality::Registers => {
+                let (definitions, mut registers) = definitions(exprs, count);
+                Expr::implies(definitions, registers.swap_remove(count))
+            }
+        }
+    }
+
+    pub fn at_most(&self, count: usize, exprs: &[Expr]) -> Expr {
+        match self {
+            _ if count >= exprs.len() => Expr::top(),
+            Cardinality::Pairwise => Expr::and(
+                &subsets(exprs, count + 1)
+                    .iter()
+                    .map(|subset| {
+                        Expr::or(&subset.iter().cloned().map(Expr::not).collect::<Vec<_>>())
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Cardinality::Sequential => {
+                Expr::not(registers(exprs, count + 1).swap_remove(count + 1))
+            }
+            Cardinality::Registers => {
+                let (definitions, mut registers) = definitions(exprs, count + 1);
+                Expr::implies(definitions, Expr::not(registers.swap_remove(count + 1)))
+            }
+        }
+    }
+
+    pub fn exactly(&self, count: usize, exprs: &[Expr]) -> Expr {
+        match self {
+            Cardinality::Pairwise => {
+                Expr::and(&[self.at_least(count, exprs), self.at_most(count, exprs)])
+            }
+            // Both bounds share the same registers
+            Cardinality::Sequential => {
+                let mut registers = registers(exprs, count + 1);
+                let above = registers.swap_remove(count + 1);
+                and(registers.swap_remove(count), Expr::not(above))
+            }
+            Cardinality::Registers => {
+                let (definitions, mut registers) = definitions(exprs, count + 1);
+                let above = registers.swap_remove(count + 1);
+                Expr::implies(
+                    definitions,
+                    and(registers.swap_remove(count), Expr::not(above)),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{expression::Expr, Set};
+
+// Distribution can blow up exponentially, so give up beyond this many clauses
+pub const MAX_CLAUSES: usize = 1 << 16;
+
+// The clauses of a normal expression, each a set of literals, dropping those
+// that contain both a literal and its inverse
+fn clauses(expr: &Expr, max_clauses: usize) -> Result<Set<Set<Expr>>, String> {
+    log::trace!("[clauses] {expr:?}");
+    match expr {
+        Expr::And(subexprs) => subexprs
+            .iter()
+            .try_fold(Set::new(), |mut clauses, subexpr| {
+                clauses.extend(self::clauses(subexpr, max_clauses)?);
+                Ok(clauses)
+            }),
+        Expr::Or(subexprs) => {
+            subexprs
+                .iter()
+                .try_fold(Set::from([Set::new()]), |clauses, subexpr| {
+                    let rights = self::clauses(subexpr, max_clauses)?;
+                    // Checked before distributing, as the product is the blow up
+                    match clauses.len().checked_mul(rights.len()) {
+                        Some(len) if len <= max_clauses => Ok(rights
+                            .iter()
+                            .flat_map(|right| {
+                                clauses
+                                    .iter()
+                                    .map(move |left| left.union(right).cloned().collect::<Set<_>>())
+                            })
+                            .filter(|clause| {
+                                !clause
+                                    .iter()
+                                    .any(|literal| clause.contains(&literal.inverse()))
+                            })
+                            .collect()),
+                        _ => Err(format!(
+                            "Distributing {expr:?} exceeds {max_clauses} clauses"
+                        )),
+                    }
+                })
+        }
+        Expr::Atom(_) | Expr::NotAtom(_) => Ok(Set::from([Set::from([expr.clone()])])),
+        _ => panic!("Expected a normal expression, but got {expr:?}"),
+    }
+}
+
+fn fresh(names: &Set<String>, index: &mut usize) -> Expr {
+    loop {
+        let name = format!("_t{index}");
+        *index += 1;
+        if !names.contains(&name) {
+            return Expr::Atom(name);
+        }
+    }
+}
+
+impl Expr {
+    pub fn to_cnf(&self) -> Result<Expr, String> {
+        self.to_cnf_within(MAX_CLAUSES)
+    }
+
+    pub fn to_cnf_within(&self, max_clauses: usize) -> Result<Expr, String> {
+        log::trace!("[to-cnf] {self:?} within {max_clauses}");
+        let clauses = clauses(&self.normal(), max_clauses)?;
+        Ok(Expr::and(
+            &clauses
+                .into_iter()
+                .map(|clause| Expr::or(&clause.into_iter().collect::<Vec<_>>()))
+                .collect::<Vec<_>>(),
+        )
+        .normal())
+    }
+
+    pub fn to_dnf(&self) -> Result<Expr, String> {
+        self.to_dnf_within(MAX_CLAUSES)
+    }
+
+    // The terms of ψ are the inverted clauses of ¬ψ
+    pub fn to_dnf_within(&self, max_terms: usize) -> Result<Expr, String> {
+        log::trace!("[to-dnf] {self:?} within {max_terms}");
+        let clauses = clauses(&self.inverse().normal(), max_terms)?;
+        Ok(Expr::or(
+            &clauses
+                .into_iter()
+                .map(|clause| Expr::and(&clause.iter().map(Expr::inverse).collect::<Vec<_>>()))
+                .collect::<Vec<_>>(),
+        )
+        .normal())
+    }
+
+    // Names each compound subexpression with a fresh atom _tN, linear in size
+    // but only equisatisfiable with the original
+    pub fn to_cnf_tseitin(&self) -> Expr {
+        log::trace!("[to-cnf-tseitin] {self:?}");
+        let normal = self.normal();
+        let names = normal.names();
+        let mut index = 0;
+        let mut clauses = vec![];
+        let root = Self::tseitin(&normal, &names, &mut index, &mut clauses);
+        clauses.push(root);
+        Expr::and(&clauses).normal()
+    }
+
+    fn tseitin(
+        expr: &Expr,
+        names: &Set<String>,
+        index: &mut usize,
+        clauses: &mut Vec<Expr>,
+    ) -> Expr {
+        let (subexprs, conjunctive) = match expr {
+            Expr::And(subexprs) => (subexprs, true),
+            Expr::Or(subexprs) => (subexprs, false),
+            _ => return expr.clone(),
+        };
+        let literals = subexprs
+            .iter()
+            .map(|subexpr| Self::tseitin(subexpr, names, index, clauses))
+            .collect::<Vec<_>>();
+        let atom = fresh(names, index);
+        // x ↔ c1 & .. & ck is (~x | ci) for each i, and (x | ~c1 | .. | ~ck),
+        // and dually for x ↔ c1 | .. | ck
+        let (each, all) = match conjunctive {
+            true => (atom.inverse(), atom.clone()),
+            false => (atom.clone(), atom.inverse()),
+        };
+        clauses.extend(literals.iter().map(|literal| match conjunctive {
+            true => Expr::or(&[each.clone(), literal.clone()]),
+            false => Expr::or(&[each.clone(), literal.inverse()]),
+        }));
+        clauses.push(Expr::or(
+            &[all]
+                .into_iter()
+                .chain(literals.iter().map(|literal| match conjunctive {
+                    true => literal.inverse(),
+                    false => literal.clone(),
+                }))
+                .collect::<Vec<_>>(),
+        ));
+        atom
+    }
+}
@@ -1,7 +1,45 @@
-use btree_dag::{AddEdge, AddVertex};
+use btree_dag::{error::Error, AddEdge, AddVertex, Connections, Vertices};
 
 use crate::{expression::Expr, Dag, Set};
 
+// The final tokens, and the dag of every step taken to reach them
+pub type Coalescence<T> = (Set<Set<T>>, Dag<Set<T>>);
+
+// btree_dag clears the edges of a vertex when it is added again, so a token
+// deduced a second time must keep the edges it already has
+fn add_token<T: Ord + Clone>(proof: &mut Dag<T>, token: T) {
+    if proof.connections(token.clone()).is_none() {
+        proof.add_vertex(token);
+    }
+}
+
+// A token deduced again from its own consequences, having been projected away
+// and so missing from the current tokens, keeps its earlier acyclic derivation
+fn add_step<T: Ord + Clone>(proof: &mut Dag<T>, from: T, to: T) {
+    match proof.add_edge(from, to) {
+        Ok(()) | Err(Error::EdgeExistsError) => (),
+        Err(err) => panic!("Failed to record step: {err:?}"),
+    }
+}
+
+// The same proof of other tokens, mapping each element of every token
+pub(crate) fn map_proof<T: Ord + Clone, U: Ord + Clone>(
+    proof: &Dag<Set<T>>,
+    f: impl Fn(&T) -> U,
+) -> Dag<Set<U>> {
+    let map = |token: &Set<T>| token.iter().map(&f).collect::<Set<_>>();
+    let mut mapped = Dag::new();
+    for token in proof.vertices() {
+        add_token(&mut mapped, map(token));
+    }
+    for token in proof.vertices() {
+        for target in proof.connections(token.clone()).into_iter().flatten() {
+            add_step(&mut mapped, map(token), map(target));
+        }
+    }
+    mapped
+}
+
 pub trait Coalesceable: Sized + Ord + Clone + std::fmt::Debug {
     fn axiom_set(&self) -> Set<Self>;
 
@@ -15,12 +53,26 @@ pub trait Coalesceable: Sized + Ord + Clone + std::fmt::Debug {
 
     fn project(&self, proof: &mut Dag<Set<Self>>, tokens: &Set<Set<Self>>) -> Set<Set<Self>>;
 
-    fn coalesce(&self) -> Option<(Set<Set<Self>>, Dag<Set<Self>>)> {
-        log::trace!("[coalesce] {self:?}");
+    // Tokens taken as proven from the given lemmas, none unless overridden
+    fn cite(&self, _proof: &mut Dag<Set<Self>>, _lemmas: &Set<Self>) -> Set<Set<Self>> {
+        Set::new()
+    }
+
+    fn coalesce(&self) -> Option<Coalescence<Self>> {
+        self.coalesce_within(Self::dim_bound(self))
+    }
+
+    fn coalesce_within(&self, dim_bound: usize) -> Option<Coalescence<Self>> {
+        self.coalesce_with(&Set::new(), dim_bound)
+    }
+
+    fn coalesce_with(&self, lemmas: &Set<Self>, dim_bound: usize) -> Option<Coalescence<Self>> {
+        log::trace!("[coalesce] {self:?} with {lemmas:?} within {dim_bound}");
         let mut proof = Dag::<Set<Self>>::new();
-        proof.add_vertex(Set::new());
+        add_token(&mut proof, Set::new());
 
         let mut tokens = Self::spawn(self, &mut proof);
+        tokens.extend(Self::cite(self, &mut proof, lemmas));
         if tokens.is_empty() {
             return None;
         }
@@ -30,8 +82,13 @@ pub trait Coalesceable: Sized + Ord + Clone + std::fmt::Debug {
             log::trace!("[coalesce] {self:?} not in {tokens:?}");
             if old_tokens == tokens {
                 let current_dim = tokens.iter().map(Set::len).fold(0, |a, b| a.max(b));
-                if current_dim <= Self::dim_bound(self) {
+                if current_dim <= dim_bound {
                     tokens = Self::project(self, &mut proof, &tokens);
+                    // Every projection has already been made, and firing on no
+                    // tokens would loop forever
+                    if tokens.is_empty() {
+                        return None;
+                    }
                 } else {
                     return None;
                 }
@@ -54,6 +111,12 @@ impl Coalesceable for Expr {
         match self {
             Expr::And(children) | Expr::Or(children) => children.clone(),
             Expr::Not(expr) => Set::from([expr.clone()]),
+            Expr::Implies(left, right) | Expr::Iff(left, right) | Expr::Xor(left, right) => {
+                Set::from([left.clone(), right.clone()])
+            }
+            Expr::AtMost(_, exprs) | Expr::AtLeast(_, exprs) | Expr::Exactly(_, exprs) => {
+                exprs.iter().cloned().map(Box::new).collect()
+            }
             _ => Set::new(),
         }
     }
@@ -66,20 +129,30 @@ impl Coalesceable for Expr {
     fn spawn(&self, proof: &mut Dag<Set<Self>>) -> Set<Set<Self>> {
         log::trace!("[spawn] {self:?}");
         let atoms = self.atoms();
-        atoms
+        let mut tokens = atoms
             .iter()
             .filter_map(|&atom| {
                 let axiom = atom.axiom_set();
                 if axiom.iter().all(|atom| atoms.contains(atom)) {
                     log::debug!("∅ =T> {axiom:?}");
-                    proof.add_vertex(axiom.clone());
-                    proof.add_edge(Set::new(), axiom.clone()).unwrap();
+                    add_token(proof, axiom.clone());
+                    add_step(proof, Set::new(), axiom.clone());
                     Some(axiom)
                 } else {
                     None
                 }
             })
-            .collect()
+            .collect::<Set<_>>();
+
+        // The empty conjunction holds vacuously
+        if self.subexprs().contains(&Expr::top()) {
+            let top = Set::from([Expr::top()]);
+            log::debug!("∅ =&> {top:?}");
+            add_token(proof, top.clone());
+            add_step(proof, Set::new(), top.clone());
+            tokens.insert(top);
+        }
+        tokens
     }
 
     fn fire(&self, proof: &mut Dag<Set<Self>>, old_tokens: &Set<Set<Self>>) -> Set<Set<Self>> {
@@ -141,19 +214,19 @@ impl Coalesceable for Expr {
                 match parent_expr {
                     Expr::And(_) if children.is_subset(&tokens)  => {
                         log::debug!("{children:?} =&> {parent_token:?}");
-                        proof.add_vertex(parent_token.clone());
+                        add_token(proof, parent_token.clone());
                         children.iter().for_each(|child| {
-                            proof.add_vertex(parent_token.clone());
-                            proof.add_edge(child.clone(), parent_token.clone()).unwrap();
+                            add_token(proof, parent_token.clone());
+                            add_step(proof, child.clone(), parent_token.clone());
                         });
                         Some(parent_token)
                     },
                     Expr::Or(_) if !children.is_disjoint(&tokens) => {
                         log::debug!("{children:?} =|> {parent_token:?}");
-                        proof.add_vertex(parent_token.clone());
+                        add_token(proof, parent_token.clone());
                         children.intersection(&tokens).for_each(|child| {
-                            proof.add_vertex(parent_token.clone());
-                            proof.add_edge(child.clone(), parent_token.clone()).unwrap();
+                            add_token(proof, parent_token.clone());
+                            add_step(proof, child.clone(), parent_token.clone());
                         });
                         Some(parent_token)
                     },
@@ -179,8 +252,8 @@ impl Coalesceable for Expr {
                         };
                         if !tokens.contains(&projection) {
                             log::debug!("{token:?} =%> {projection:?}");
-                            proof.add_vertex(projection.clone());
-                            proof.add_edge(token.clone(), projection.clone()).unwrap();
+                            add_token(proof, projection.clone());
+                            add_step(proof, token.clone(), projection.clone());
                             Some(projection)
                         } else {
                             None
@@ -190,4 +263,19 @@ impl Coalesceable for Expr {
             })
             .collect()
     }
+
+    fn cite(&self, proof: &mut Dag<Set<Self>>, lemmas: &Set<Self>) -> Set<Set<Self>> {
+        log::trace!("[cite] {self:?} with {lemmas:?}");
+        self.subexprs()
+            .into_iter()
+            .filter(|&subexpr| lemmas.contains(subexpr))
+            .map(|lemma| {
+                let token = Set::from([lemma.to_owned()]);
+                log::debug!("∅ =L> {token:?}");
+                add_token(proof, token.clone());
+                add_step(proof, Set::new(), token.clone());
+                token
+            })
+            .collect()
+    }
 }
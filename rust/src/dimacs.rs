@@ -0,0 +1,151 @@
+use crate::{expression::Expr, Map};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Encoding {
+    // Equivalent, but may grow exponentially
+    Distribute,
+    // Equisatisfiable, introducing fresh atoms _tN
+    Tseitin,
+}
+
+// Variables are named by their index, unless a comment line
+// `c var <index> <name>` names them as written by `to_dimacs`, with the name
+// running to the end of the line
+pub fn parse_dimacs(input: &str) -> Result<Expr, String> {
+    log::trace!("[parse-dimacs] {input:?}");
+    let mut names = Map::<i64, String>::new();
+    let mut header = None;
+    let mut clauses = vec![];
+    let mut clause = vec![];
+
+    for (number, line) in input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+    {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        match fields.as_slice() {
+            [] => continue,
+            // SATLIB instances end with a lone %
+            ["%", ..] => break,
+            ["c", "var", index, _, ..] if index.parse::<i64>().is_ok() => {
+                // The name is the rest of the line, whitespace and all
+                let name = fields[..3].iter().fold(line, |rest, field| {
+                    rest.trim_start().strip_prefix(field).unwrap_or(rest)
+                });
+                names.insert(index.parse().unwrap(), name.trim().to_string());
+            }
+            ["c", ..] => continue,
+            ["p", "cnf", variables, count] if header.is_none() => {
+                let parse = |field: &str| {
+                    field
+                        .parse::<usize>()
+                        .map_err(|_| format!("Line {number}: Expected a count, got {field:?}"))
+                };
+                header = Some((parse(variables)?, parse(count)?));
+            }
+            ["p", ..] => return Err(format!("Line {number}: Unexpected problem line {line:?}")),
+            literals => {
+                let (variables, _) = header.ok_or(format!(
+                    "Line {number}: Expected problem line before clauses"
+                ))?;
+                for literal in literals {
+                    let literal = literal.parse::<i64>().map_err(|_| {
+                        format!("Line {number}: Expected a literal, got {literal:?}")
+                    })?;
+                    if literal.unsigned_abs() as usize > variables {
+                        return Err(format!(
+                            "Line {number}: Literal {literal} exceeds {variables} variables"
+                        ));
+                    }
+                    match literal {
+                        0 => clauses.push(std::mem::take(&mut clause)),
+                        _ => clause.push(literal),
+                    }
+                }
+            }
+        }
+    }
+
+    let (_, count) = header.ok_or("Expected problem line, got none")?;
+    if !clause.is_empty() {
+        clauses.push(clause);
+    }
+    if clauses.len() != count {
+        return Err(format!("Expected {count} clauses, got {}", clauses.len()));
+    }
+
+    let name = |variable: i64| {
+        names
+            .get(&variable)
+            .cloned()
+            .unwrap_or_else(|| variable.to_string())
+    };
+    Ok(Expr::and(
+        &clauses
+            .iter()
+            .map(|clause| {
+                Expr::or(
+                    &clause
+                        .iter()
+                        .map(|&literal| match literal {
+                            literal if literal > 0 => Expr::Atom(name(literal)),
+                            literal => Expr::NotAtom(name(-literal)),
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+    .normal())
+}
+
+impl Expr {
+    pub fn to_dimacs(&self, encoding: Encoding) -> Result<String, String> {
+        log::trace!("[to-dimacs] {self:?} by {encoding:?}");
+        let cnf = match encoding {
+            Encoding::Distribute => self.to_cnf()?,
+            Encoding::Tseitin => self.to_cnf_tseitin(),
+        };
+        let indices = cnf
+            .names()
+            .into_iter()
+            .zip(1..)
+            .collect::<Map<String, i64>>();
+        let clauses = match &cnf {
+            Expr::And(clauses) => clauses.iter().map(|clause| &**clause).collect(),
+            clause => vec![clause],
+        };
+
+        let literal = |literal: &Expr| match literal {
+            Expr::Atom(name) => Ok(indices[name]),
+            Expr::NotAtom(name) => Ok(-indices[name]),
+            _ => Err(format!("Expected a literal in {cnf:?}, got {literal:?}")),
+        };
+        let lines = clauses
+            .iter()
+            .map(|clause| {
+                match clause {
+                    Expr::Or(literals) => literals.iter().map(|expr| literal(expr)).collect(),
+                    expr => literal(expr).map(|literal| vec![literal]),
+                }
+                .map(|literals: Vec<i64>| {
+                    literals
+                        .iter()
+                        .chain([&0])
+                        .map(i64::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(indices
+            .iter()
+            .map(|(name, index)| format!("c var {index} {name}"))
+            .chain([format!("p cnf {} {}", indices.len(), lines.len())])
+            .chain(lines)
+            .map(|line| line + "\n")
+            .collect())
+    }
+}
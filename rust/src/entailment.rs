@@ -0,0 +1,64 @@
+use crate::{
+    coalesceable::Coalesceable,
+    expression::Expr,
+    proof::{Proof, Rule},
+    sequent::{Sequent, SequentProof},
+    Set,
+};
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+pub struct Entailment {
+    pub core: Vec<usize>,
+    pub proof: SequentProof,
+}
+
+fn axiom_literals(proof: &Proof) -> Set<Expr> {
+    match proof.rule {
+        Rule::Axiom => proof.token.clone(),
+        _ => proof.premises.iter().flat_map(axiom_literals).collect(),
+    }
+}
+
+fn subset(premises: &[Expr], core: &[usize]) -> Vec<Expr> {
+    core.iter().map(|&index| premises[index].clone()).collect()
+}
+
+// The core is irredundant rather than minimum: dropping any one of its premises
+// breaks the entailment, but a smaller core may still exist elsewhere
+pub fn entails(premises: &[Expr], goal: &Expr) -> Option<Entailment> {
+    log::trace!("[entails] {premises:?} |- {goal:?}");
+    let goal = [goal.clone()];
+    let proof = Sequent::new(premises, &goal).prove()?;
+
+    // Seed with the premises that reached an axiom of the proof
+    let literals = axiom_literals(&proof.proof);
+    let seed = (0..premises.len())
+        .filter(|&index| {
+            Expr::not(premises[index].clone())
+                .normal()
+                .atoms()
+                .iter()
+                .any(|&atom| literals.contains(atom))
+        })
+        .collect::<Vec<_>>();
+    let mut core = match Sequent::new(&subset(premises, &seed), &goal).prove() {
+        Some(_) => seed,
+        None => (0..premises.len()).collect(),
+    };
+
+    let mut index = 0;
+    while index < core.len() {
+        let mut candidate = core.clone();
+        candidate.remove(index);
+        match Sequent::new(&subset(premises, &candidate), &goal).coalesce() {
+            Some(_) => {
+                log::debug!("[entails] {:?} is redundant", premises[core[index]]);
+                core = candidate;
+            }
+            None => index += 1,
+        }
+    }
+
+    let proof = Sequent::new(&subset(premises, &core), &goal).prove()?;
+    Some(Entailment { core, proof })
+}
@@ -1,26 +1,41 @@
-use crate::Set;
+use crate::{cardinality::Cardinality, coalesceable::Coalesceable, Map, Set};
+
+// Beyond this many names, equivalence gives up on finding a counterexample
+pub(crate) const MAX_TABLE_NAMES: usize = 16;
 
 // TODO: This could be arena-allocated
 // i.e. store vec walk of tree and tree of vec indexes
 #[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     And(Set<Box<Expr>>),
     Or(Set<Box<Expr>>),
     Not(Box<Expr>),
+    Implies(Box<Expr>, Box<Expr>),
+    Iff(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    AtMost(usize, Vec<Expr>),
+    AtLeast(usize, Vec<Expr>),
+    Exactly(usize, Vec<Expr>),
     Atom(String),
     NotAtom(String),
 }
 
 impl std::fmt::Debug for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operand = |expr: &Expr| match expr {
+            Self::And(exprs) | Self::Or(exprs) if !exprs.is_empty() => format!("({expr:?})"),
+            Self::Implies(..) | Self::Iff(..) | Self::Xor(..) => format!("({expr:?})"),
+            _ => format!("{expr:?}"),
+        };
         match self {
+            // The empty conjunction is true, the empty disjunction false
+            Self::And(exprs) if exprs.is_empty() => f.write_str("T"),
+            Self::Or(exprs) if exprs.is_empty() => f.write_str("F"),
             Self::And(exprs) | Self::Or(exprs) => f.write_str(
                 &exprs
                     .iter()
-                    .map(|expr| match *expr.to_owned() {
-                        Self::And(_) | Self::Or(_) => format!("({expr:?})"),
-                        _ => format!("{expr:?}"),
-                    })
+                    .map(|expr| operand(expr))
                     .collect::<Vec<_>>()
                     .join(match self {
                         Self::And(_) => " & ",
@@ -29,6 +44,30 @@ impl std::fmt::Debug for Expr {
                     }),
             ),
             Self::Not(expr) => f.write_fmt(format_args!("~({expr:?})")),
+            Self::Implies(left, right) => {
+                f.write_fmt(format_args!("{} > {}", operand(left), operand(right)))
+            }
+            Self::Iff(left, right) => {
+                f.write_fmt(format_args!("{} = {}", operand(left), operand(right)))
+            }
+            Self::Xor(left, right) => {
+                f.write_fmt(format_args!("{} ^ {}", operand(left), operand(right)))
+            }
+            Self::AtMost(count, exprs)
+            | Self::AtLeast(count, exprs)
+            | Self::Exactly(count, exprs) => f.write_fmt(format_args!(
+                "{}({count}; {})",
+                match self {
+                    Self::AtMost(..) => "atmost",
+                    Self::AtLeast(..) => "atleast",
+                    _ => "exactly",
+                },
+                exprs
+                    .iter()
+                    .map(|expr| format!("{expr:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
             Self::Atom(name) => f.write_fmt(format_args!("{name}")),
             Self::NotAtom(name) => f.write_fmt(format_args!("~{name}")),
         }
@@ -48,6 +87,42 @@ impl Expr {
         Self::Not(expr.into())
     }
 
+    pub fn implies(left: Expr, right: Expr) -> Self {
+        Self::Implies(left.into(), right.into())
+    }
+
+    pub fn iff(left: Expr, right: Expr) -> Self {
+        Self::Iff(left.into(), right.into())
+    }
+
+    pub fn xor(left: Expr, right: Expr) -> Self {
+        Self::Xor(left.into(), right.into())
+    }
+
+    pub fn at_most(count: usize, exprs: &[Expr]) -> Self {
+        Self::AtMost(count, exprs.to_vec())
+    }
+
+    pub fn at_least(count: usize, exprs: &[Expr]) -> Self {
+        Self::AtLeast(count, exprs.to_vec())
+    }
+
+    pub fn exactly(count: usize, exprs: &[Expr]) -> Self {
+        Self::Exactly(count, exprs.to_vec())
+    }
+
+    pub fn top() -> Self {
+        Self::And(Set::new())
+    }
+
+    pub fn bottom() -> Self {
+        Self::Or(Set::new())
+    }
+
+    pub fn is_literal(&self) -> bool {
+        matches!(self, Expr::Atom(_) | Expr::NotAtom(_))
+    }
+
     pub fn inverse(&self) -> Self {
         log::trace!("[inverse] {self:?}");
         match self {
@@ -64,16 +139,27 @@ impl Expr {
                     .collect(),
             ),
             Expr::Not(subexpr) => *subexpr.clone(),
+            Expr::Implies(left, right) => Expr::and(&[*left.clone(), right.inverse()]),
+            Expr::Iff(left, right) => Expr::xor(*left.clone(), *right.clone()),
+            Expr::Xor(left, right) => Expr::iff(*left.clone(), *right.clone()),
+            Expr::AtMost(count, exprs) => Expr::at_least(count + 1, exprs),
+            Expr::AtLeast(0, _) => Expr::bottom(),
+            Expr::AtLeast(count, exprs) => Expr::at_most(count - 1, exprs),
+            Expr::Exactly(0, exprs) => Expr::at_least(1, exprs),
+            Expr::Exactly(count, exprs) => Expr::or(&[
+                Expr::at_most(count - 1, exprs),
+                Expr::at_least(count + 1, exprs),
+            ]),
             Expr::Atom(name) => Expr::NotAtom(name.to_string()),
             Expr::NotAtom(name) => Expr::Atom(name.to_string()),
         }
     }
 
-    fn normal_and(subexprs: Set<Box<Expr>>) -> Expr {
+    fn normal_and(subexprs: Set<Box<Expr>>, cardinality: Cardinality) -> Expr {
         log::trace!("[normal-and] {subexprs:?}");
         let norm: Set<Box<Expr>> = subexprs
             .iter()
-            .map(|subexpr| subexpr.clone().normal())
+            .map(|subexpr| subexpr.normal_with(cardinality))
             .flat_map(|subexpr| match subexpr {
                 Expr::And(subexprs) => subexprs,
                 expr => Set::from([expr.into()]),
@@ -86,11 +172,11 @@ impl Expr {
         }
     }
 
-    fn normal_or(subexprs: Set<Box<Expr>>) -> Expr {
+    fn normal_or(subexprs: Set<Box<Expr>>, cardinality: Cardinality) -> Expr {
         log::trace!("[normal-or] {subexprs:?}");
         let norm: Set<Box<Expr>> = subexprs
             .iter()
-            .map(|subexpr| subexpr.clone().normal())
+            .map(|subexpr| subexpr.normal_with(cardinality))
             .flat_map(|subexpr| match subexpr {
                 Expr::Or(subexprs) => subexprs,
                 expr => Set::from([expr.into()]),
@@ -104,27 +190,283 @@ impl Expr {
     }
 
     pub fn normal(&self) -> Self {
+        self.normal_with(Cardinality::default())
+    }
+
+    pub fn normal_with(&self, cardinality: Cardinality) -> Self {
         log::trace!("[normal] {self:?}");
         match self {
-            Expr::And(subexprs) => Expr::normal_and(subexprs.clone()),
-            Expr::Or(subexprs) => Expr::normal_or(subexprs.clone()),
-            Expr::Not(expr) => expr.inverse().normal(),
+            Expr::And(subexprs) => Expr::normal_and(subexprs.clone(), cardinality),
+            Expr::Or(subexprs) => Expr::normal_or(subexprs.clone(), cardinality),
+            Expr::Not(expr) => expr.inverse().normal_with(cardinality),
+            // Implication and biconditional are eliminated only here, so that
+            // they survive parsing and printing
+            Expr::Implies(left, right) => {
+                Expr::or(&[left.inverse(), *right.clone()]).normal_with(cardinality)
+            }
+            Expr::Iff(left, right) => Expr::and(&[
+                Expr::or(&[left.inverse(), *right.clone()]),
+                Expr::or(&[*left.clone(), right.inverse()]),
+            ])
+            .normal_with(cardinality),
+            Expr::Xor(left, right) => Expr::and(&[
+                Expr::or(&[*left.clone(), *right.clone()]),
+                Expr::or(&[left.inverse(), right.inverse()]),
+            ])
+            .normal_with(cardinality),
+            Expr::AtMost(count, exprs) => {
+                cardinality.at_most(*count, exprs).normal_with(cardinality)
+            }
+            Expr::AtLeast(count, exprs) => {
+                cardinality.at_least(*count, exprs).normal_with(cardinality)
+            }
+            Expr::Exactly(count, exprs) => {
+                cardinality.exactly(*count, exprs).normal_with(cardinality)
+            }
             Expr::Atom(name) => Expr::Atom(name.to_string()),
             Expr::NotAtom(name) => Expr::NotAtom(name.to_string()),
         }
     }
 
+    // Normalises, then repeats complementation, absorption and constant
+    // folding until nothing changes, preserving equivalence throughout
+    pub fn simplify(&self) -> Self {
+        log::trace!("[simplify] {self:?}");
+        let mut expr = self.normal();
+        loop {
+            let simpler = expr.simplify_once();
+            if simpler == expr {
+                return expr;
+            }
+            expr = simpler;
+        }
+    }
+
+    fn simplify_once(&self) -> Self {
+        log::trace!("[simplify-once] {self:?}");
+        let (subexprs, conjunctive) = match self {
+            Expr::And(subexprs) => (subexprs, true),
+            Expr::Or(subexprs) => (subexprs, false),
+            _ => return self.clone(),
+        };
+        let (unit, zero) = match conjunctive {
+            true => (Expr::top(), Expr::bottom()),
+            false => (Expr::bottom(), Expr::top()),
+        };
+        let subexprs = subexprs
+            .iter()
+            .map(|subexpr| subexpr.simplify_once())
+            .collect::<Set<_>>();
+
+        // Complementation, a & ~a = F and a | ~a = T
+        let literals = subexprs
+            .iter()
+            .filter(|subexpr| subexpr.is_literal())
+            .cloned()
+            .collect::<Set<_>>();
+        if literals
+            .iter()
+            .any(|literal| literals.contains(&literal.inverse()))
+        {
+            return zero;
+        }
+
+        // Each sibling literal is true within a conjunction, false within a
+        // disjunction, so a & (a | b) = a & T and a | (a & b) = a | F
+        let subexprs = subexprs
+            .into_iter()
+            .map(|subexpr| match subexpr.is_literal() {
+                true => subexpr,
+                false => subexpr.assume(&literals, conjunctive).normal(),
+            })
+            .collect::<Set<_>>();
+
+        // Constant folding
+        if subexprs.contains(&zero) {
+            return zero;
+        }
+        let subexprs = subexprs
+            .into_iter()
+            .filter(|subexpr| *subexpr != unit)
+            .collect::<Set<_>>();
+
+        // Absorption, (a | b) & (a | b | c) = a | b and dually
+        let operands = |expr: &Expr| match (expr, conjunctive) {
+            (Expr::Or(subexprs), true) | (Expr::And(subexprs), false) => {
+                subexprs.iter().map(|subexpr| *subexpr.to_owned()).collect()
+            }
+            _ => Set::from([expr.to_owned()]),
+        };
+        let absorbed = subexprs
+            .iter()
+            .filter(|&subexpr| {
+                subexprs
+                    .iter()
+                    .any(|other| other != subexpr && operands(other).is_subset(&operands(subexpr)))
+            })
+            .cloned()
+            .collect::<Set<_>>();
+        let subexprs = subexprs
+            .difference(&absorbed)
+            .map(|subexpr| subexpr.to_owned().into())
+            .collect();
+
+        match conjunctive {
+            true => Expr::normal_and(subexprs, Cardinality::default()),
+            false => Expr::normal_or(subexprs, Cardinality::default()),
+        }
+    }
+
+    // Substitutes the given value for each of the literals, and its inverse for
+    // each of their inverses
+    fn assume(&self, literals: &Set<Expr>, value: bool) -> Self {
+        let constant = |value| match value {
+            true => Expr::top(),
+            false => Expr::bottom(),
+        };
+        match self {
+            _ if literals.contains(self) => constant(value),
+            Expr::Atom(_) | Expr::NotAtom(_) if literals.contains(&self.inverse()) => {
+                constant(!value)
+            }
+            Expr::And(subexprs) => Expr::And(
+                subexprs
+                    .iter()
+                    .map(|subexpr| subexpr.assume(literals, value).into())
+                    .collect(),
+            ),
+            Expr::Or(subexprs) => Expr::Or(
+                subexprs
+                    .iter()
+                    .map(|subexpr| subexpr.assume(literals, value).into())
+                    .collect(),
+            ),
+            Expr::Not(expr) => Expr::not(expr.assume(literals, value)),
+            Expr::Implies(left, right) => {
+                Expr::implies(left.assume(literals, value), right.assume(literals, value))
+            }
+            Expr::Iff(left, right) => {
+                Expr::iff(left.assume(literals, value), right.assume(literals, value))
+            }
+            Expr::Xor(left, right) => {
+                Expr::xor(left.assume(literals, value), right.assume(literals, value))
+            }
+            Expr::AtMost(count, exprs)
+            | Expr::AtLeast(count, exprs)
+            | Expr::Exactly(count, exprs) => {
+                let exprs = exprs
+                    .iter()
+                    .map(|expr| expr.assume(literals, value))
+                    .collect::<Vec<_>>();
+                match self {
+                    Expr::AtMost(..) => Expr::at_most(*count, &exprs),
+                    Expr::AtLeast(..) => Expr::at_least(*count, &exprs),
+                    _ => Expr::exactly(*count, &exprs),
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+
+    // Replaces atoms simultaneously, so that replacements are not themselves
+    // substituted into, and ~a by the inverse of the replacement for a
+    pub fn substitute(&self, replacements: &Map<String, Expr>) -> Self {
+        self.fold(&mut |expr: Expr| match &expr {
+            Expr::Atom(name) => replacements.get(name).cloned().unwrap_or(expr),
+            Expr::NotAtom(name) => replacements.get(name).map(Expr::inverse).unwrap_or(expr),
+            _ => expr,
+        })
+    }
+
+    pub fn rename_atoms(&self, names: &Map<String, String>) -> Self {
+        self.substitute(
+            &names
+                .iter()
+                .map(|(from, to)| (from.to_owned(), Expr::Atom(to.to_owned())))
+                .collect(),
+        )
+    }
+
     pub fn names(&self) -> Set<String> {
         log::trace!("[names] {self:?}");
+        let mut names = Set::new();
+        self.walk(&mut |expr: &Expr| {
+            if let Expr::Atom(name) | Expr::NotAtom(name) = expr {
+                names.insert(name.to_string());
+            }
+        });
+        names
+    }
+
+    pub fn evaluate(&self, assignment: &Map<String, bool>) -> Result<bool, String> {
         match self {
-            Expr::And(subexprs) | Expr::Or(subexprs) => {
-                subexprs.iter().flat_map(|expr| expr.names()).collect()
+            Expr::And(subexprs) => subexprs
+                .iter()
+                .try_fold(true, |value, expr| Ok(value & expr.evaluate(assignment)?)),
+            Expr::Or(subexprs) => subexprs
+                .iter()
+                .try_fold(false, |value, expr| Ok(value | expr.evaluate(assignment)?)),
+            Expr::Not(expr) => Ok(!expr.evaluate(assignment)?),
+            Expr::Implies(left, right) => {
+                Ok(!left.evaluate(assignment)? | right.evaluate(assignment)?)
+            }
+            Expr::Iff(left, right) => Ok(left.evaluate(assignment)? == right.evaluate(assignment)?),
+            Expr::Xor(left, right) => Ok(left.evaluate(assignment)? != right.evaluate(assignment)?),
+            Expr::AtMost(count, exprs)
+            | Expr::AtLeast(count, exprs)
+            | Expr::Exactly(count, exprs) => {
+                let mut values = exprs.iter().map(|expr| expr.evaluate(assignment));
+                let trues =
+                    values.try_fold(0, |trues, value| Ok::<_, String>(trues + value? as usize))?;
+                Ok(match self {
+                    Expr::AtMost(..) => trues <= *count,
+                    Expr::AtLeast(..) => trues >= *count,
+                    _ => trues == *count,
+                })
+            }
+            Expr::Atom(name) | Expr::NotAtom(name) => {
+                let value = assignment
+                    .get(name)
+                    .ok_or(format!("No value assigned to {name}"))?;
+                Ok(matches!(self, Expr::Atom(_)) == *value)
             }
-            Expr::Atom(name) | Expr::NotAtom(name) => Set::from([name.to_string()]),
-            Expr::Not(expr) => expr.names(),
         }
     }
 
+    // Proves self = other, else finds an assignment on which they differ, so
+    // long as there are few enough names to search the truth table for one
+    pub fn equivalent(&self, other: &Expr) -> Result<(), Option<Map<String, bool>>> {
+        log::trace!("[equivalent] {self:?} = {other:?}");
+        let equivalence = Expr::iff(self.clone(), other.clone()).normal();
+        if equivalence.coalesce().is_some() {
+            return Ok(());
+        }
+
+        let names = self
+            .names()
+            .union(&other.names())
+            .cloned()
+            .collect::<Vec<_>>();
+        if names.len() > MAX_TABLE_NAMES {
+            log::debug!(
+                "[equivalent] {} names are too many to tabulate",
+                names.len()
+            );
+            return Err(None);
+        }
+        // The truth table is the final word should coalescence fall short
+        (0..1u32 << names.len())
+            .map(|row| {
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| (name.to_owned(), row & (1 << index) != 0))
+                    .collect::<Map<_, _>>()
+            })
+            .find(|assignment| self.evaluate(assignment) != other.evaluate(assignment))
+            .map_or(Ok(()), |assignment| Err(Some(assignment)))
+    }
+
     pub fn atoms(&self) -> Set<&Expr> {
         log::trace!("[atoms] {self:?}");
         match self {
@@ -133,6 +475,70 @@ impl Expr {
             }
             Expr::Atom(_) | Expr::NotAtom(_) => Set::from([self]),
             Expr::Not(expr) => expr.atoms(),
+            Expr::Implies(left, right) | Expr::Iff(left, right) | Expr::Xor(left, right) => {
+                left.atoms().union(&right.atoms()).cloned().collect()
+            }
+            Expr::AtMost(_, exprs) | Expr::AtLeast(_, exprs) | Expr::Exactly(_, exprs) => {
+                exprs.iter().flat_map(|expr| expr.atoms()).collect()
+            }
+        }
+    }
+
+    // FNV-1a over a canonical encoding, so stable across runs and platforms
+    // unlike std's randomly-keyed hashers
+    pub fn digest(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+        let mut bytes = vec![];
+        self.encode(&mut bytes);
+        bytes.iter().fold(OFFSET, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Expr::And(subexprs) | Expr::Or(subexprs) => {
+                bytes.push(match self {
+                    Expr::And(_) => 0,
+                    _ => 1,
+                });
+                bytes.extend((subexprs.len() as u64).to_le_bytes());
+                subexprs.iter().for_each(|subexpr| subexpr.encode(bytes));
+            }
+            Expr::Not(expr) => {
+                bytes.push(2);
+                expr.encode(bytes);
+            }
+            Expr::Implies(left, right) | Expr::Iff(left, right) | Expr::Xor(left, right) => {
+                bytes.push(match self {
+                    Expr::Implies(..) => 5,
+                    Expr::Iff(..) => 6,
+                    _ => 7,
+                });
+                left.encode(bytes);
+                right.encode(bytes);
+            }
+            Expr::AtMost(count, exprs)
+            | Expr::AtLeast(count, exprs)
+            | Expr::Exactly(count, exprs) => {
+                bytes.push(match self {
+                    Expr::AtMost(..) => 8,
+                    Expr::AtLeast(..) => 9,
+                    _ => 10,
+                });
+                bytes.extend((*count as u64).to_le_bytes());
+                bytes.extend((exprs.len() as u64).to_le_bytes());
+                exprs.iter().for_each(|expr| expr.encode(bytes));
+            }
+            Expr::Atom(name) | Expr::NotAtom(name) => {
+                bytes.push(match self {
+                    Expr::Atom(_) => 3,
+                    _ => 4,
+                });
+                bytes.extend((name.len() as u64).to_le_bytes());
+                bytes.extend(name.as_bytes());
+            }
         }
     }
 
@@ -151,7 +557,62 @@ impl Expr {
                 .chain([vec![self]])
                 .collect(),
             Expr::Atom(_) | Expr::NotAtom(_) => Set::from([vec![self]]),
-            Expr::Not(_) => panic!("CBA"),
+            _ => panic!("CBA"),
+        }
+    }
+
+    fn has_derived(&self) -> bool {
+        match self {
+            Expr::And(subexprs) | Expr::Or(subexprs) => {
+                subexprs.iter().any(|subexpr| subexpr.has_derived())
+            }
+            Expr::Not(expr) => expr.has_derived(),
+            Expr::Atom(_) | Expr::NotAtom(_) => false,
+            _ => true,
+        }
+    }
+
+    // Maps the normal form of each subformula, as it occurs in a proof of the
+    // whole, back to the subformula as written
+    pub fn originals(&self) -> Map<Expr, Expr> {
+        log::trace!("[originals] {self:?}");
+        let mut originals = Map::new();
+        self.collect_originals(true, &mut originals);
+        originals
+    }
+
+    fn collect_originals(&self, positive: bool, originals: &mut Map<Expr, Expr>) {
+        match self {
+            Expr::And(subexprs) | Expr::Or(subexprs) => subexprs
+                .iter()
+                .for_each(|subexpr| subexpr.collect_originals(positive, originals)),
+            Expr::Not(expr) => expr.collect_originals(!positive, originals),
+            Expr::Implies(left, right) => {
+                left.collect_originals(!positive, originals);
+                right.collect_originals(positive, originals);
+            }
+            Expr::Iff(left, right) | Expr::Xor(left, right) => {
+                [left, right].iter().for_each(|expr| {
+                    expr.collect_originals(true, originals);
+                    expr.collect_originals(false, originals);
+                })
+            }
+            Expr::AtMost(_, exprs) | Expr::AtLeast(_, exprs) | Expr::Exactly(_, exprs) => {
+                exprs.iter().for_each(|expr| {
+                    expr.collect_originals(true, originals);
+                    expr.collect_originals(false, originals);
+                })
+            }
+            Expr::Atom(_) | Expr::NotAtom(_) => (),
+        }
+        // Only formulas using the connectives that normal() eliminates are
+        // kept, the innermost where several share a normal form
+        let original = match positive {
+            true => self.clone(),
+            false => Expr::not(self.clone()),
+        };
+        if self.has_derived() {
+            originals.entry(original.normal()).or_insert(original);
         }
     }
 }
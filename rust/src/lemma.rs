@@ -0,0 +1,131 @@
+use crate::{
+    coalesceable::Coalesceable,
+    expression::Expr,
+    proof::{Proof, Rule},
+    rewrite::{matchings, Matching},
+    Map, Set,
+};
+
+// Proven formulas, keyed by their normal form, that later proofs may cite, also
+// as instances with their atoms renamed
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug, Default)]
+pub struct LemmaStore {
+    lemmas: Map<Expr, Proof>,
+}
+
+impl LemmaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lemmas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lemmas.is_empty()
+    }
+
+    pub fn get(&self, expr: &Expr) -> Option<&Proof> {
+        self.lemmas.get(&expr.normal())
+    }
+
+    pub fn lemmas(&self) -> impl Iterator<Item = (&Expr, &Proof)> {
+        self.lemmas.iter()
+    }
+
+    pub fn prove(&mut self, expr: &Expr) -> Option<Proof> {
+        log::trace!("[prove] {expr:?} with {} lemmas", self.len());
+        let expr = expr.normal();
+        if let Some(proof) = self.lemmas.get(&expr) {
+            log::debug!("[prove] {expr:?} is a lemma");
+            return Some(proof.clone());
+        }
+
+        // Instances join the store only once they serve a proof
+        let instances = self.instances(&expr);
+        let lemmas = self
+            .lemmas
+            .keys()
+            .chain(instances.keys())
+            .cloned()
+            .collect::<Set<_>>();
+        let (_, dag) = expr.coalesce_with(&lemmas, expr.dim_bound())?;
+        let proof = Proof::reconstruct(&expr, &dag)?;
+        self.lemmas.extend(instances);
+        self.lemmas.insert(expr, proof.clone());
+        Some(proof)
+    }
+
+    // Each subformula of expr that is a lemma with its atoms renamed one to one,
+    // with the lemma's proof renamed likewise
+    fn instances(&self, expr: &Expr) -> Map<Expr, Proof> {
+        let mut instances = Map::new();
+        for (lemma, proof) in &self.lemmas {
+            let pattern = lemma.rename_atoms(
+                &lemma
+                    .names()
+                    .into_iter()
+                    .map(|name| (name.clone(), format!("?{name}")))
+                    .collect(),
+            );
+            for subexpr in expr.subexprs() {
+                if self.lemmas.contains_key(subexpr) || instances.contains_key(subexpr) {
+                    continue;
+                }
+                let names = matchings(&pattern, subexpr, &Matching::new())
+                    .into_iter()
+                    .filter_map(|matching| {
+                        matching
+                            .into_iter()
+                            .map(|(name, atom)| match atom {
+                                Expr::Atom(atom) => Some((name[1..].to_string(), atom)),
+                                _ => None,
+                            })
+                            .collect::<Option<Map<_, _>>>()
+                    })
+                    .find(|names| names.values().collect::<Set<_>>().len() == names.len());
+                if let Some(names) = names {
+                    log::debug!("[instances] {subexpr:?} is an instance of {lemma:?}");
+                    instances.insert(subexpr.clone(), rename(proof, &names));
+                }
+            }
+        }
+        instances
+    }
+
+    // Replace each cited lemma with its own proof, recursively
+    pub fn expand(&self, proof: &Proof) -> Proof {
+        log::trace!("[expand] {proof:?}");
+        match &proof.rule {
+            Rule::Lemma(lemma) => match self.lemmas.get(lemma) {
+                Some(lemma) => self.expand(lemma),
+                None => proof.clone(),
+            },
+            _ => Proof {
+                premises: proof
+                    .premises
+                    .iter()
+                    .map(|premise| self.expand(premise))
+                    .collect(),
+                ..proof.clone()
+            },
+        }
+    }
+}
+
+fn rename(proof: &Proof, names: &Map<String, String>) -> Proof {
+    fn exprs(proof: &Proof) -> Set<&Expr> {
+        proof
+            .token
+            .iter()
+            .chain(proof.premises.iter().flat_map(exprs))
+            .collect()
+    }
+    proof.relabel(
+        &exprs(proof)
+            .into_iter()
+            .map(|expr| (expr.clone(), expr.rename_atoms(names).normal()))
+            .collect(),
+    )
+}
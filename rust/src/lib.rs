@@ -15,6 +15,22 @@ pub type Set<T> = BTreeSet<T>;
 pub type Map<K, V> = BTreeMap<K, V>;
 pub type Dag<T> = BTreeDag<T>;
 
+pub mod batch;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod cardinality;
+pub mod clausal;
 pub mod coalesceable;
+pub mod dimacs;
+pub mod entailment;
 pub mod expression;
-pub mod parseable;
\ No newline at end of file
+pub mod lemma;
+pub mod parseable;
+pub mod prolog;
+pub mod proof;
+pub mod rewrite;
+pub mod sequent;
+pub mod smtlib;
+pub mod texable;
+pub mod theory;
+pub mod tptp;
\ No newline at end of file
@@ -1,4 +1,13 @@
-use crate::expression::Expr;
+use crate::{expression::Expr, rewrite::Pattern, sequent::Sequent, Map};
+
+const TURNSTILES: [&str; 4] = ["<-->", "-->", "|-", "⊢"];
+
+const CARDINALITIES: [&str; 3] = ["atmost", "atleast", "exactly"];
+
+const BINDERS: [&str; 2] = ["AND", "OR"];
+
+// Values of the index variables of enclosing big operators
+type Bindings = Map<String, i64>;
 
 pub trait Parseable: Sized {
     fn inner_parse(input: &str, parent: &str) -> Result<Self, String>;
@@ -8,33 +17,177 @@ pub trait Parseable: Sized {
     }
 }
 
-fn nongreedy_parse(input: &str, parent: &str) -> Result<(Expr, usize), String> {
+fn nongreedy_parse(
+    input: &str,
+    parent: &str,
+    bindings: &Bindings,
+) -> Result<(Expr, usize), String> {
     log::trace!("[nongreedy-parse] {input:?} in {parent:?}");
     match input.chars().nth(0) {
         None => Err(format!("Expected expression in {parent:?}, got empty")),
         Some('(') => {
             let index = 1 + find_closing(&input.chars().skip(1).collect::<String>())?;
             assert_eq!(input.chars().nth(index), Some(')'));
-            let expr = Expr::inner_parse(
+            let expr = expr_parse(
                 &input.chars().take(index).skip(1).collect::<String>(),
                 input,
+                bindings,
             )?;
             Ok((expr, index))
         }
         Some('~') => {
-            let (expr, index) = nongreedy_parse(&input.chars().skip(1).collect::<String>(), input)?;
+            let (expr, index) =
+                nongreedy_parse(&input.chars().skip(1).collect::<String>(), input, bindings)?;
             Ok((Expr::Not(Box::new(expr)), index + 1))
         }
+        _ if CARDINALITIES
+            .iter()
+            .any(|name| input.starts_with(&format!("{name}("))) =>
+        {
+            cardinality_parse(input, parent, bindings)
+        }
         _ => {
-            let (atom, index) = input
-                .find(' ')
-                .map(|index| (input.chars().take(index).collect::<String>(), index))
-                .unwrap_or((input.to_string(), input.len()));
-            Ok((Expr::Atom(atom), index))
+            // Spaces may separate the indexes of an atom p[i, j]
+            let mut depth = 0;
+            let index = input
+                .chars()
+                .position(|c| {
+                    match c {
+                        '[' => depth += 1,
+                        ']' => depth -= 1,
+                        _ => (),
+                    }
+                    c == ' ' && depth == 0
+                })
+                .unwrap_or(input.chars().count());
+            let atom = input.chars().take(index).collect::<String>();
+            // T and F are true and false, as Expr prints them
+            let expr = match atom.as_str() {
+                "T" => Expr::top(),
+                "F" => Expr::bottom(),
+                _ => Expr::Atom(unreserved(atom_parse(&atom, parent, bindings)?)?),
+            };
+            Ok((expr, index))
         }
     }
 }
 
+// Atoms starting with _ are reserved for the fresh atoms of the Tseitin and
+// register encodings, which would otherwise collide with them
+pub(crate) fn unreserved(name: String) -> Result<String, String> {
+    match name.starts_with('_') {
+        true => Err(format!(
+            "Atom {name} starts with _, reserved for fresh atoms"
+        )),
+        false => Ok(name),
+    }
+}
+
+// Resolves the indexes of p[i, j + 1] to p[1,3] in the given bindings
+fn atom_parse(atom: &str, parent: &str, bindings: &Bindings) -> Result<String, String> {
+    let Some((name, indexes)) = atom.split_once('[') else {
+        return Ok(atom.to_string());
+    };
+    let indexes = indexes
+        .strip_suffix(']')
+        .ok_or(format!("Expected ] in {parent:?} but got {atom:?}"))?
+        .split(',')
+        .map(|index| index_parse(index, parent, bindings).map(|index| index.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("{name}[{}]", indexes.join(",")))
+}
+
+// A sum or difference of integers and bound index variables
+fn index_parse(input: &str, parent: &str, bindings: &Bindings) -> Result<i64, String> {
+    log::trace!("[index-parse] {input:?} in {parent:?}");
+    let mut terms = vec![];
+    let mut rest = input.trim();
+    loop {
+        let (sign, tail) = match rest.strip_prefix('-') {
+            Some(tail) => (-1, tail),
+            None => (1, rest.strip_prefix('+').unwrap_or(rest)),
+        };
+        let tail = tail.trim_start();
+        let len = tail.find(['+', '-']).unwrap_or(tail.len());
+        let term = tail[..len].trim();
+        let value = match term.parse::<i64>() {
+            Ok(value) => value,
+            Err(_) => *bindings.get(term).ok_or(format!(
+                "Expected an integer or bound index in {parent:?} but got {term:?}"
+            ))?,
+        };
+        terms.push(sign * value);
+        rest = &tail[len..];
+        if rest.is_empty() {
+            return Ok(terms.iter().sum());
+        }
+    }
+}
+
+// AND i in lo..hi: body, and likewise OR, expanded over the inclusive range
+fn binder_parse(input: &str, parent: &str, bindings: &Bindings) -> Option<Result<Expr, String>> {
+    let (binder, rest) = input.split_once(' ')?;
+    if !BINDERS.contains(&binder) {
+        return None;
+    }
+    let (header, body) = rest.split_once(':')?;
+    let (variable, range) = header.split_once(" in ")?;
+    let (low, high) = range.split_once("..")?;
+    log::trace!("[binder-parse] {input:?} in {parent:?}");
+    let variable = variable.trim();
+    let expand = || {
+        if !variable.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !variable
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(format!(
+                "Expected an index variable in {parent:?} but got {variable:?}"
+            ));
+        }
+        let exprs = (index_parse(low, input, bindings)?..=index_parse(high, input, bindings)?)
+            .map(|value| {
+                let mut bindings = bindings.clone();
+                bindings.insert(variable.to_string(), value);
+                expr_parse(body, input, &bindings)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(match (binder, exprs.as_slice()) {
+            (_, [expr]) => expr.clone(),
+            ("AND", _) => Expr::and(&exprs),
+            _ => Expr::or(&exprs),
+        })
+    };
+    Some(expand())
+}
+
+// One of atmost(k; a, b, ..), atleast(k; ..) or exactly(k; ..)
+fn cardinality_parse(
+    input: &str,
+    parent: &str,
+    bindings: &Bindings,
+) -> Result<(Expr, usize), String> {
+    log::trace!("[cardinality-parse] {input:?} in {parent:?}");
+    let open = input.find('(').unwrap();
+    let inner = input.chars().skip(open + 1).collect::<String>();
+    let close = find_closing(&inner)?;
+    let inner = inner.chars().take(close).collect::<String>();
+    let (count, operands) = inner
+        .split_once(';')
+        .ok_or(format!("Expected ; in {parent:?} but got {input:?}"))?;
+    let count = count
+        .trim()
+        .parse::<usize>()
+        .map_err(|err| format!("Expected a count in {parent:?} but got {count:?}, {err}"))?;
+    let exprs = list_parse(operands, input, bindings)?;
+    let expr = match &input[..open] {
+        "atmost" => Expr::at_most(count, &exprs),
+        "atleast" => Expr::at_least(count, &exprs),
+        _ => Expr::exactly(count, &exprs),
+    };
+    Ok((expr, open + 1 + close))
+}
+
 fn operator_parse(
     input: &str,
     left_expr: Expr,
@@ -57,21 +210,42 @@ fn operator_parse(
         )),
         Some('>') => Ok((
             Some(Box::new(move |right_expr| {
-                Expr::or(&[Expr::not(left_expr.clone()), right_expr])
+                Expr::implies(left_expr.clone(), right_expr)
+            })),
+            0,
+        )),
+        Some('<') => Ok((
+            Some(Box::new(move |right_expr| {
+                Expr::implies(right_expr, left_expr.clone())
             })),
             0,
         )),
         Some('=') => Ok((
             Some(Box::new(move |right_expr| {
-                Expr::and(&[
-                    Expr::or(&[Expr::not(left_expr.clone()), right_expr.clone()]),
-                    Expr::or(&[left_expr.clone(), Expr::not(right_expr)]),
-                ])
+                Expr::iff(left_expr.clone(), right_expr)
             })),
             0,
         )),
+        Some('#' | '^') => Ok((
+            Some(Box::new(move |right_expr| {
+                Expr::xor(left_expr.clone(), right_expr)
+            })),
+            0,
+        )),
+        Some('~') if input.starts_with("~&") => Ok((
+            Some(Box::new(move |right_expr| {
+                Expr::not(Expr::and(&[left_expr.clone(), right_expr]))
+            })),
+            1,
+        )),
+        Some('~') if input.starts_with("~|") => Ok((
+            Some(Box::new(move |right_expr| {
+                Expr::not(Expr::or(&[left_expr.clone(), right_expr]))
+            })),
+            1,
+        )),
         _ => Err(format!(
-            "Expected [empty, |, &, >, =] in {parent:?} but got {input:?}"
+            "Expected [empty, |, &, >, <, =, #, ^, ~&, ~|] in {parent:?} but got {input:?}"
         )),
     }
 }
@@ -97,26 +271,180 @@ fn find_closing(input: &str) -> Result<usize, String> {
     }
 }
 
+fn expr_parse(input: &str, parent: &str, bindings: &Bindings) -> Result<Expr, String> {
+    log::trace!("[expr-parse] {input:?} in {parent:?}");
+
+    let left_inp = input.trim_start();
+    if let Some(expr) = binder_parse(left_inp, parent, bindings) {
+        return expr;
+    }
+    let (left_expr, left_idx) = nongreedy_parse(left_inp, parent, bindings)?;
+
+    let mid_inp_string = left_inp.chars().skip(left_idx + 1).collect::<String>();
+    let mid_inp = &mid_inp_string.trim_start();
+
+    let (maybe_expr_fn, mid_idx) = operator_parse(mid_inp, left_expr.clone(), left_inp)?;
+
+    Ok(match maybe_expr_fn {
+        Some(expr_fn) => {
+            let right_inp_string = mid_inp.chars().skip(mid_idx + 1).collect::<String>();
+            let right_inp = &right_inp_string.trim_start();
+            let right_expr = expr_parse(right_inp, left_inp, bindings)?;
+            expr_fn(right_expr)
+        }
+        None => left_expr,
+    })
+}
+
 impl Parseable for Expr {
     fn inner_parse(input: &str, parent: &str) -> Result<Self, String> {
         log::trace!("[inner-parse] {input:?} in {parent:?}");
+        expr_parse(input, parent, &Bindings::new())
+    }
+}
 
-        let left_inp = input.trim_start();
-        let (left_expr, left_idx) = nongreedy_parse(left_inp, &parent)?;
+fn find_top_level<'a>(input: &str, patterns: &[&'a str]) -> Option<(usize, &'a str)> {
+    let mut depth = 0;
+    input.char_indices().find_map(|(index, c)| {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ => (),
+        }
+        match depth {
+            0 => patterns
+                .iter()
+                .find(|&pattern| input[index..].starts_with(pattern))
+                .map(|&pattern| (index, pattern)),
+            _ => None,
+        }
+    })
+}
+
+pub(crate) fn split_top_level<'a>(input: &'a str, separator: &str) -> Vec<&'a str> {
+    match find_top_level(input, &[separator]) {
+        Some((index, _)) => [&input[..index]]
+            .into_iter()
+            .chain(split_top_level(
+                &input[index + separator.len()..],
+                separator,
+            ))
+            .collect(),
+        None => vec![input],
+    }
+}
 
-        let mid_inp_string = left_inp.chars().skip(left_idx + 1).collect::<String>();
-        let mid_inp = &mid_inp_string.trim_start();
+fn list_parse(input: &str, parent: &str, bindings: &Bindings) -> Result<Vec<Expr>, String> {
+    log::trace!("[list-parse] {input:?} in {parent:?}");
+    match input.trim() {
+        "" => Ok(vec![]),
+        input => split_top_level(input, ",")
+            .into_iter()
+            .map(|item| expr_parse(item, input, bindings))
+            .collect(),
+    }
+}
 
-        let (maybe_expr_fn, mid_idx) = operator_parse(mid_inp, left_expr.clone(), left_inp)?;
+fn turnstile_parse(input: &str, parent: &str) -> Result<(Sequent, bool), String> {
+    log::trace!("[turnstile-parse] {input:?} in {parent:?}");
+    let (index, turnstile) = find_top_level(input, &TURNSTILES).ok_or(format!(
+        "Expected one of {TURNSTILES:?} in {parent:?} but got {input:?}"
+    ))?;
+    let sequent = Sequent {
+        antecedent: list_parse(&input[..index], input, &Bindings::new())?,
+        succedent: list_parse(&input[index + turnstile.len()..], input, &Bindings::new())?,
+    };
+    Ok((sequent, turnstile == "<-->"))
+}
 
-        Ok(match maybe_expr_fn {
-            Some(expr_fn) => {
-                let right_inp_string = mid_inp.chars().skip(mid_idx + 1).collect::<String>();
-                let right_inp = &right_inp_string.trim_start();
-                let right_expr = Self::inner_parse(right_inp, left_inp)?;
-                expr_fn(right_expr)
+impl Parseable for Sequent {
+    fn inner_parse(input: &str, parent: &str) -> Result<Self, String> {
+        log::trace!("[inner-parse] {input:?} in {parent:?}");
+        match turnstile_parse(input, parent)? {
+            (sequent, false) => Ok(sequent),
+            (_, true) => Err(format!(
+                "Expected a one-way sequent in {parent:?} but got {input:?}, see parse_sequents"
+            )),
+        }
+    }
+}
+
+impl Parseable for Pattern {
+    fn inner_parse(input: &str, parent: &str) -> Result<Self, String> {
+        log::trace!("[inner-parse] {input:?} in {parent:?}");
+        let (index, arrow) = find_top_level(input, &["=>"])
+            .ok_or(format!("Expected => in {parent:?} but got {input:?}"))?;
+        Pattern::new(
+            Expr::inner_parse(input[..index].trim(), input)?,
+            Expr::inner_parse(input[index + arrow.len()..].trim(), input)?,
+        )
+    }
+}
+
+// Both directions of A1, .., Am <--> B1, .., Bn are proven, as by the Prolog seqprover
+pub fn parse_sequents(input: &str) -> Result<Vec<Sequent>, String> {
+    match turnstile_parse(input, "[root]")? {
+        (sequent, false) => Ok(vec![sequent]),
+        (sequent, true) => Ok(vec![sequent.clone(), sequent.converse()]),
+    }
+}
+
+// The C prover's syntax, strictly binary and parenthesised, with single-character
+// atoms, ^ v > < = # for and, or, implies, implied by, equivalent and not
+// equivalent, and T and F for true and false
+fn compact_parse(input: &[char], parent: &str) -> Result<(Expr, usize), String> {
+    log::trace!("[compact-parse] {input:?} in {parent:?}");
+    match input {
+        [] => Err(format!("Expected formula in {parent:?}, got empty")),
+        ['(', rest @ ..] => {
+            let (left, left_len) = compact_parse(rest, parent)?;
+            let op = *rest
+                .get(left_len)
+                .ok_or(format!("Expected operator in {parent:?}, got end of input"))?;
+            let (right, right_len) = compact_parse(&rest[left_len + 1..], parent)?;
+            let len = 1 + left_len + 1 + right_len;
+            match input.get(len) {
+                Some(')') => (),
+                found => return Err(format!("Expected ) in {parent:?}, got {found:?}")),
             }
-            None => left_expr,
-        })
+            let expr = match op {
+                '^' => Expr::and(&[left, right]),
+                'v' => Expr::or(&[left, right]),
+                '>' => Expr::implies(left, right),
+                '<' => Expr::implies(right, left),
+                '=' => Expr::iff(left, right),
+                '#' => Expr::xor(left, right),
+                op => {
+                    return Err(format!(
+                        "Expected one of [^, v, >, <, =, #] in {parent:?}, got {op:?}"
+                    ))
+                }
+            };
+            Ok((expr, len + 1))
+        }
+        ['~', '(' | 'T' | 'F', ..] => {
+            let (expr, len) = compact_parse(&input[1..], parent)?;
+            Ok((Expr::not(expr), len + 1))
+        }
+        ['~', atom, ..] if !"()^v><=#~".contains(*atom) => Ok((Expr::NotAtom(atom.to_string()), 2)),
+        ['T', ..] => Ok((Expr::top(), 1)),
+        ['F', ..] => Ok((Expr::bottom(), 1)),
+        [symbol, ..] if "()^v><=#~".contains(*symbol) => Err(format!(
+            "Expected one of [(, ~, T, F] or an atom in {parent:?}, got {symbol:?}"
+        )),
+        [atom, ..] => Ok((Expr::Atom(atom.to_string()), 1)),
+    }
+}
+
+pub fn parse_compact(input: &str) -> Result<Expr, String> {
+    let input = input.trim();
+    let chars = input.chars().collect::<Vec<_>>();
+    let (expr, len) = compact_parse(&chars, input)?;
+    match len == chars.len() {
+        true => Ok(expr),
+        false => Err(format!(
+            "Expected end of input in {input:?}, got {:?}",
+            chars[len..].iter().collect::<String>()
+        )),
     }
 }
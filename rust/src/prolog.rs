@@ -0,0 +1,202 @@
+use crate::{expression::Expr, parseable::unreserved, sequent::Sequent};
+
+// Longest first, so that each is matched before its prefixes
+const PUNCTUATION: [&str; 12] = [
+    "<-->", "-->", "<->", "->", "/\\", "\\/", "~", "(", ")", "[", "]", ",",
+];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+    Atom(String),
+    Variable(String),
+    Punctuation(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    log::trace!("[tokenize] {input:?}");
+    let mut tokens = vec![];
+    // A query may end with a full stop
+    let mut rest = input.trim().strip_suffix('.').unwrap_or(input.trim());
+    loop {
+        rest = rest.trim_start();
+        let Some(next) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        let (token, len) = match next {
+            '\'' => {
+                let len = 1 + rest[1..]
+                    .find('\'')
+                    .ok_or(format!("Unterminated quote in {rest:?}"))?;
+                (Token::Atom(rest[1..len].to_string()), len + 1)
+            }
+            '_' | 'a'..='z' | 'A'..='Z' | '0'..='9' => {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let word = rest[..len].to_string();
+                match next {
+                    '_' | 'A'..='Z' => (Token::Variable(word), len),
+                    _ => (Token::Atom(word), len),
+                }
+            }
+            _ => {
+                let punctuation = PUNCTUATION
+                    .iter()
+                    .find(|&&punctuation| rest.starts_with(punctuation))
+                    .ok_or(format!("Unexpected {next:?} in {rest:?}"))?;
+                (Token::Punctuation(punctuation), punctuation.len())
+            }
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or("Expected a token, got end of input".to_string())?;
+        self.index += 1;
+        Ok(token)
+    }
+
+    fn accept(&mut self, punctuation: &str) -> bool {
+        match self.peek() {
+            Some(Token::Punctuation(found)) if *found == punctuation => {
+                self.index += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, punctuation: &str) -> Result<(), String> {
+        match self.next()? {
+            Token::Punctuation(found) if found == punctuation => Ok(()),
+            token => Err(format!("Expected {punctuation:?}, got {token:?}")),
+        }
+    }
+
+    fn end(&self) -> Result<(), String> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(format!("Expected end of input, got {token:?}")),
+        }
+    }
+
+    // -> and <-> at 900, xfy
+    fn formula(&mut self) -> Result<Expr, String> {
+        let left = self.junction()?;
+        if self.accept("->") {
+            let right = self.formula()?;
+            Ok(Expr::implies(left, right))
+        } else if self.accept("<->") {
+            let right = self.formula()?;
+            Ok(Expr::iff(left, right))
+        } else {
+            Ok(left)
+        }
+    }
+
+    // /\ and \/ together at 850, xfy, so a /\ b \/ c is a /\ (b \/ c)
+    fn junction(&mut self) -> Result<Expr, String> {
+        let left = self.unary()?;
+        if self.accept("/\\") {
+            Ok(Expr::and(&[left, self.junction()?]))
+        } else if self.accept("\\/") {
+            Ok(Expr::or(&[left, self.junction()?]))
+        } else {
+            Ok(left)
+        }
+    }
+
+    // ~ at 800, fy
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next()? {
+            Token::Punctuation("~") => Ok(Expr::not(self.unary()?)),
+            Token::Punctuation("(") => {
+                let expr = self.formula()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Atom(name) if self.peek() == Some(&Token::Punctuation("(")) => Err(format!(
+                "Expected a propositional atom, got predicate {name}"
+            )),
+            Token::Atom(name) if name == "top" => Ok(Expr::top()),
+            Token::Atom(name) if name == "bot" => Ok(Expr::bottom()),
+            Token::Atom(name) => unreserved(name).map(Expr::Atom),
+            Token::Variable(name) => Err(format!(
+                "Expected a propositional atom, got variable {name}"
+            )),
+            token => Err(format!("Expected a formula, got {token:?}")),
+        }
+    }
+
+    // Either [A1, .., Am] or A1, .., Am, as by convert_to_list
+    fn list(&mut self) -> Result<Vec<Expr>, String> {
+        let bracketed = self.accept("[");
+        let mut exprs = vec![];
+        match self.peek() {
+            Some(Token::Punctuation("]")) if bracketed => (),
+            None | Some(Token::Punctuation("-->" | "<-->")) if !bracketed => (),
+            _ => loop {
+                exprs.push(self.formula()?);
+                if !self.accept(",") {
+                    break;
+                }
+            },
+        }
+        if bracketed {
+            self.expect("]")?;
+        }
+        Ok(exprs)
+    }
+}
+
+pub fn parse_prolog_formula(input: &str) -> Result<Expr, String> {
+    log::trace!("[parse-prolog-formula] {input:?}");
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        index: 0,
+    };
+    let expr = parser.formula()?;
+    parser.end()?;
+    Ok(expr)
+}
+
+// Both directions of Xs <--> Ys are proven, as by seq_execute
+pub fn parse_prolog(input: &str) -> Result<Vec<Sequent>, String> {
+    log::trace!("[parse-prolog] {input:?}");
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        index: 0,
+    };
+    let antecedent = parser.list()?;
+    let bidirectional = match parser.next()? {
+        Token::Punctuation("-->") => false,
+        Token::Punctuation("<-->") => true,
+        token => return Err(format!("Expected \"-->\" or \"<-->\", got {token:?}")),
+    };
+    let succedent = parser.list()?;
+    parser.end()?;
+
+    let sequent = Sequent {
+        antecedent,
+        succedent,
+    };
+    match bidirectional {
+        false => Ok(vec![sequent]),
+        true => Ok(vec![sequent.clone(), sequent.converse()]),
+    }
+}
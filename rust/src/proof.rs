@@ -0,0 +1,330 @@
+//! Proofs reconstructed from a coalescence [`Dag`].
+//!
+//! With the `serde` feature, a [`ProofGraph`] serializes to JSON as
+//!
+//! ```json
+//! {
+//!   "nodes": [{ "id": 0, "token": [], "rule": null },
+//!             { "id": 1, "token": [{ "Atom": "a" }, { "NotAtom": "a" }], "rule": "Axiom" }],
+//!   "edges": [{ "from": 0, "to": 1 }]
+//! }
+//! ```
+//!
+//! where each token is a list of [`Expr`], each `Expr` is externally tagged
+//! (`{"And": [..]}`, `{"Or": [..]}`, `{"Not": ..}`, `{"Implies": [.., ..]}`,
+//! `{"Iff": [.., ..]}`, `{"Xor": [.., ..]}`, `{"AtMost": [count, [..]]}`,
+//! `{"AtLeast": [count, [..]]}`, `{"Exactly": [count, [..]]}`,
+//! `{"Atom": name}`, `{"NotAtom": name}`), and each rule is one of `"Axiom"`,
+//! `{"Lemma": expr}`, `{"And": expr}`, `{"Or": expr}` or `{"Project": expr}`
+//! naming the principal formula.
+//! Node ids are indexes into `nodes`, and node `0` is always the empty token.
+//! A token set (`Set<Set<Expr>>`) is a list of tokens.
+
+use btree_dag::{AddEdge, AddVertex, Connections, Vertices};
+
+use crate::{expression::Expr, Dag, Map, Set};
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rule {
+    Axiom,
+    Lemma(Expr),
+    And(Expr),
+    Or(Expr),
+    Project(Expr),
+}
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Proof {
+    pub token: Set<Expr>,
+    pub rule: Rule,
+    pub premises: Vec<Proof>,
+}
+
+// Each vertex of a coalescence dag records only its predecessors, so the rule
+// that produced it is recovered from the shape of those predecessors:
+//   ∅ =T> {a, ~a}, ∅ =L> {l}, ∅ =&> {T}, {Γ, a} .. {Γ, b} =&> {Γ, a & b}, {Γ, a} =|> {Γ, a | b}, {Γ} =%> {Γ, a}
+pub fn infer(token: &Set<Expr>, predecessors: &Set<&Set<Expr>>) -> Option<(Rule, Vec<Set<Expr>>)> {
+    log::trace!("[infer] {token:?} from {predecessors:?}");
+    if predecessors.contains(&Set::new()) {
+        // Axioms are always pairs, cited lemmas and the empty conjunction alone
+        return match token.first() {
+            Some(top) if token.len() == 1 && *top == Expr::top() => {
+                Some((Rule::And(Expr::top()), vec![]))
+            }
+            Some(lemma) if token.len() == 1 => Some((Rule::Lemma(lemma.to_owned()), vec![])),
+            _ => Some((Rule::Axiom, vec![])),
+        };
+    }
+
+    token
+        .iter()
+        .find_map(|expr| {
+            let contexts = [
+                {
+                    let mut partial = token.to_owned();
+                    partial.remove(expr);
+                    partial
+                },
+                token.to_owned(),
+            ];
+            let children = match expr {
+                Expr::And(children) | Expr::Or(children) => children,
+                _ => return None,
+            };
+            contexts.iter().find_map(|context| {
+                let premises = children
+                    .iter()
+                    .map(|child| {
+                        let mut partial = context.to_owned();
+                        partial.insert(*child.to_owned());
+                        partial
+                    })
+                    .collect::<Set<_>>();
+                match expr {
+                    Expr::And(_)
+                        if premises
+                            .iter()
+                            .all(|premise| predecessors.contains(premise)) =>
+                    {
+                        Some((Rule::And(expr.to_owned()), premises.into_iter().collect()))
+                    }
+                    Expr::Or(_) => premises
+                        .into_iter()
+                        .find(|premise| predecessors.contains(premise))
+                        .map(|premise| (Rule::Or(expr.to_owned()), vec![premise])),
+                    _ => None,
+                }
+            })
+        })
+        .or_else(|| {
+            token.iter().find_map(|expr| {
+                let mut premise = token.to_owned();
+                premise.remove(expr);
+                if predecessors.contains(&premise) {
+                    Some((Rule::Project(expr.to_owned()), vec![premise]))
+                } else {
+                    None
+                }
+            })
+        })
+}
+
+pub fn predecessors(proof: &Dag<Set<Expr>>) -> Map<&Set<Expr>, Set<&Set<Expr>>> {
+    proof
+        .vertices()
+        .into_iter()
+        .flat_map(|vertex| {
+            proof
+                .connections(vertex.clone())
+                .into_iter()
+                .flatten()
+                .map(move |target| (target, vertex))
+        })
+        .fold(Map::new(), |mut predecessors, (target, vertex)| {
+            predecessors
+                .entry(target)
+                .or_insert_with(Set::new)
+                .insert(vertex);
+            predecessors
+        })
+}
+
+fn token_string(token: &Set<Expr>) -> String {
+    token
+        .iter()
+        .map(|expr| format!("{expr:?}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Rule::Axiom => "T",
+            Rule::Lemma(_) => "L",
+            Rule::And(_) => "&",
+            Rule::Or(_) => "|",
+            Rule::Project(_) => "%",
+        })
+    }
+}
+
+impl std::fmt::Display for Proof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut stack = vec![(0, self)];
+        while let Some((depth, proof)) = stack.pop() {
+            f.write_fmt(format_args!(
+                "{}⊢ {} [{}]\n",
+                "  ".repeat(depth),
+                token_string(&proof.token),
+                proof.rule
+            ))?;
+            stack.extend(
+                proof
+                    .premises
+                    .iter()
+                    .rev()
+                    .map(|premise| (depth + 1, premise)),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Proof {
+    pub fn reconstruct(goal: &Expr, proof: &Dag<Set<Expr>>) -> Option<Self> {
+        log::trace!("[reconstruct] {goal:?}");
+        Self::backtrack(Set::from([goal.clone()]), &predecessors(proof))
+    }
+
+    // Replaces each normalised formula by the original it came from, as given
+    // by Expr::originals, for display only
+    pub fn relabel(&self, originals: &Map<Expr, Expr>) -> Self {
+        let relabel = |expr: &Expr| originals.get(expr).unwrap_or(expr).to_owned();
+        Proof {
+            token: self.token.iter().map(relabel).collect(),
+            rule: match &self.rule {
+                Rule::Axiom => Rule::Axiom,
+                Rule::Lemma(expr) => Rule::Lemma(relabel(expr)),
+                Rule::And(expr) => Rule::And(relabel(expr)),
+                Rule::Or(expr) => Rule::Or(relabel(expr)),
+                Rule::Project(expr) => Rule::Project(relabel(expr)),
+            },
+            premises: self
+                .premises
+                .iter()
+                .map(|premise| premise.relabel(originals))
+                .collect(),
+        }
+    }
+
+    fn backtrack(
+        token: Set<Expr>,
+        predecessors: &Map<&Set<Expr>, Set<&Set<Expr>>>,
+    ) -> Option<Self> {
+        log::trace!("[backtrack] {token:?}");
+        let (rule, premises) = infer(&token, predecessors.get(&token)?)?;
+        let premises = premises
+            .into_iter()
+            .map(|premise| Self::backtrack(premise, predecessors))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Proof {
+            token,
+            rule,
+            premises,
+        })
+    }
+}
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProofNode {
+    pub id: usize,
+    pub token: Set<Expr>,
+    pub rule: Option<Rule>,
+}
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProofEdge {
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProofGraph {
+    pub nodes: Vec<ProofNode>,
+    pub edges: Vec<ProofEdge>,
+}
+
+impl From<&Dag<Set<Expr>>> for ProofGraph {
+    fn from(proof: &Dag<Set<Expr>>) -> Self {
+        log::trace!("[proof-graph]");
+        let predecessors = predecessors(proof);
+        let vertices = proof.vertices().into_iter().collect::<Vec<_>>();
+        let ids = vertices
+            .iter()
+            .enumerate()
+            .map(|(id, &vertex)| (vertex, id))
+            .collect::<Map<_, _>>();
+
+        let nodes = vertices
+            .iter()
+            .enumerate()
+            .map(|(id, &vertex)| ProofNode {
+                id,
+                token: vertex.to_owned(),
+                rule: predecessors
+                    .get(vertex)
+                    .and_then(|predecessors| infer(vertex, predecessors))
+                    .map(|(rule, _)| rule),
+            })
+            .collect();
+        let edges = vertices
+            .iter()
+            .flat_map(|&vertex| {
+                proof
+                    .connections(vertex.clone())
+                    .into_iter()
+                    .flatten()
+                    .map(|target| ProofEdge {
+                        from: ids[vertex],
+                        to: ids[target],
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        ProofGraph { nodes, edges }
+    }
+}
+
+impl ProofGraph {
+    pub fn dot(&self) -> String {
+        log::trace!("[dot] {self:?}");
+        let escape = |label: String| label.replace('\\', "\\\\").replace('"', "\\\"");
+        let nodes = self.nodes.iter().map(|node| {
+            let label = match &node.rule {
+                Some(rule) => format!("⊢ {} [{rule}]", token_string(&node.token)),
+                None => "∅".to_string(),
+            };
+            format!("    N{}[label=\"{}\"];", node.id, escape(label))
+        });
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| format!("    N{} -> N{};", edge.from, edge.to));
+        ["digraph Proof {".to_string()]
+            .into_iter()
+            .chain(nodes)
+            .chain(edges)
+            .chain(["}".to_string()])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn dag(&self) -> Result<Dag<Set<Expr>>, String> {
+        log::trace!("[dag] {self:?}");
+        let mut proof = Dag::new();
+        self.nodes.iter().for_each(|node| {
+            proof.add_vertex(node.token.clone());
+        });
+        self.edges.iter().try_for_each(|edge| {
+            let token = |id: usize| {
+                self.nodes
+                    .iter()
+                    .find(|node| node.id == id)
+                    .map(|node| node.token.clone())
+                    .ok_or(format!("Expected node {id} in proof graph"))
+            };
+            proof
+                .add_edge(token(edge.from)?, token(edge.to)?)
+                .map(|_| ())
+                .map_err(|_| format!("Invalid edge {edge:?} in proof graph"))
+        })?;
+        Ok(proof)
+    }
+}
@@ -0,0 +1,421 @@
+use std::mem::discriminant;
+
+use crate::{coalesceable::Coalesceable, expression::Expr, Map, Set};
+
+// Passes after which rules are assumed not to terminate
+const MAX_PASSES: usize = 1 << 10;
+
+pub trait Visitor {
+    // Returning false skips the operands of expr, and leaving it
+    fn enter(&mut self, _expr: &Expr) -> bool {
+        true
+    }
+
+    fn leave(&mut self, _expr: &Expr) {}
+}
+
+impl<F: FnMut(&Expr)> Visitor for F {
+    fn enter(&mut self, expr: &Expr) -> bool {
+        self(expr);
+        true
+    }
+}
+
+pub trait Fold {
+    fn fold(&mut self, expr: Expr) -> Expr;
+}
+
+impl<F: FnMut(Expr) -> Expr> Fold for F {
+    fn fold(&mut self, expr: Expr) -> Expr {
+        self(expr)
+    }
+}
+
+pub trait Rewrite {
+    // None where the rule does not apply
+    fn rewrite(&self, expr: &Expr) -> Option<Expr>;
+}
+
+impl<F: Fn(&Expr) -> Option<Expr>> Rewrite for F {
+    fn rewrite(&self, expr: &Expr) -> Option<Expr> {
+        self(expr)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Order {
+    // Operands are rewritten before the expressions containing them
+    #[default]
+    BottomUp,
+    // Expressions are rewritten before their operands, including any
+    // operands introduced by the rewrite
+    TopDown,
+}
+
+#[derive(Default)]
+pub struct Rewriter {
+    pub rules: Vec<Box<dyn Rewrite>>,
+    pub order: Order,
+}
+
+// A rewrite lhs => rhs in the formula syntax, where atoms named ?x are
+// metavariables standing for any formula
+#[derive(Clone, PartialEq, Eq)]
+pub struct Pattern {
+    lhs: Expr,
+    rhs: Expr,
+}
+
+// Values of the metavariables of a pattern
+pub(crate) type Matching = Map<String, Expr>;
+
+enum Frame<T> {
+    Enter(T),
+    Leave(T, usize),
+}
+
+fn unpack<const N: usize>(operands: Vec<Expr>) -> [Expr; N] {
+    operands.try_into().unwrap_or_else(|operands: Vec<Expr>| {
+        panic!("Expected {N} operands, got {}", operands.len())
+    })
+}
+
+impl Expr {
+    pub fn operands(&self) -> Vec<&Expr> {
+        match self {
+            Expr::And(exprs) | Expr::Or(exprs) => exprs.iter().map(|expr| &**expr).collect(),
+            Expr::Not(expr) => vec![expr],
+            Expr::Implies(left, right) | Expr::Iff(left, right) | Expr::Xor(left, right) => {
+                vec![left, right]
+            }
+            Expr::AtMost(_, exprs) | Expr::AtLeast(_, exprs) | Expr::Exactly(_, exprs) => {
+                exprs.iter().collect()
+            }
+            Expr::Atom(_) | Expr::NotAtom(_) => vec![],
+        }
+    }
+
+    // The operands moved out, leaving an expression of the same connective
+    // to be rebuilt by with_operands
+    fn into_operands(mut self) -> (Expr, Vec<Expr>) {
+        let operands = match &mut self {
+            Expr::And(exprs) | Expr::Or(exprs) => std::mem::take(exprs)
+                .into_iter()
+                .map(|expr| *expr)
+                .collect(),
+            Expr::Not(expr) => vec![std::mem::replace(&mut **expr, Expr::top())],
+            Expr::Implies(left, right) | Expr::Iff(left, right) | Expr::Xor(left, right) => vec![
+                std::mem::replace(&mut **left, Expr::top()),
+                std::mem::replace(&mut **right, Expr::top()),
+            ],
+            Expr::AtMost(_, exprs) | Expr::AtLeast(_, exprs) | Expr::Exactly(_, exprs) => {
+                std::mem::take(exprs)
+            }
+            Expr::Atom(_) | Expr::NotAtom(_) => vec![],
+        };
+        (self, operands)
+    }
+
+    // The same connective, and count for cardinality constraints, over the
+    // given operands, in the order of operands()
+    pub fn with_operands(&self, operands: Vec<Expr>) -> Expr {
+        match self {
+            Expr::And(_) => Expr::And(operands.into_iter().map(Box::new).collect()),
+            Expr::Or(_) => Expr::Or(operands.into_iter().map(Box::new).collect()),
+            Expr::Not(_) => {
+                let [expr] = unpack(operands);
+                Expr::not(expr)
+            }
+            Expr::Implies(..) => {
+                let [left, right] = unpack(operands);
+                Expr::implies(left, right)
+            }
+            Expr::Iff(..) => {
+                let [left, right] = unpack(operands);
+                Expr::iff(left, right)
+            }
+            Expr::Xor(..) => {
+                let [left, right] = unpack(operands);
+                Expr::xor(left, right)
+            }
+            Expr::AtMost(count, _) => Expr::AtMost(*count, operands),
+            Expr::AtLeast(count, _) => Expr::AtLeast(*count, operands),
+            Expr::Exactly(count, _) => Expr::Exactly(*count, operands),
+            Expr::Atom(_) | Expr::NotAtom(_) => self.clone(),
+        }
+    }
+
+    // Depth first, without recursion, so deep formulas cannot overflow the stack
+    pub fn walk(&self, visitor: &mut impl Visitor) {
+        let mut stack = vec![Frame::Enter(self)];
+        while let Some(frame) = stack.pop() {
+            match frame {
+                Frame::Enter(expr) => {
+                    if visitor.enter(expr) {
+                        let operands = expr.operands();
+                        stack.push(Frame::Leave(expr, operands.len()));
+                        stack.extend(operands.into_iter().rev().map(Frame::Enter));
+                    }
+                }
+                Frame::Leave(expr, _) => visitor.leave(expr),
+            }
+        }
+    }
+
+    // Bottom up, each expression folded once its operands have been
+    pub fn fold(&self, folder: &mut impl Fold) -> Expr {
+        let mut stack = vec![Frame::Enter(self)];
+        let mut folded = vec![];
+        while let Some(frame) = stack.pop() {
+            match frame {
+                Frame::Enter(expr) => {
+                    let operands = expr.operands();
+                    stack.push(Frame::Leave(expr, operands.len()));
+                    stack.extend(operands.into_iter().rev().map(Frame::Enter));
+                }
+                Frame::Leave(expr, count) => {
+                    let operands = folded.split_off(folded.len() - count);
+                    folded.push(folder.fold(expr.with_operands(operands)));
+                }
+            }
+        }
+        folded.pop().unwrap()
+    }
+}
+
+impl Rewriter {
+    pub fn new(rules: Vec<Box<dyn Rewrite>>, order: Order) -> Self {
+        Rewriter { rules, order }
+    }
+
+    fn rewrite_once(&self, expr: &Expr) -> Option<Expr> {
+        self.rules.iter().find_map(|rule| rule.rewrite(expr))
+    }
+
+    // Whether any rule applied, as comparing deep formulas would recurse
+    fn pass(&self, expr: Expr) -> (Expr, bool) {
+        let mut changed = false;
+        let mut rewrite = |expr: Expr| match self.rewrite_once(&expr) {
+            Some(rewritten) => {
+                changed = true;
+                rewritten
+            }
+            None => expr,
+        };
+        let expr = match self.order {
+            Order::BottomUp => expr.fold(&mut rewrite),
+            Order::TopDown => {
+                let mut stack = vec![Frame::Enter(expr)];
+                let mut rewritten = vec![];
+                while let Some(frame) = stack.pop() {
+                    match frame {
+                        Frame::Enter(expr) => {
+                            let (expr, operands) = rewrite(expr).into_operands();
+                            stack.push(Frame::Leave(expr, operands.len()));
+                            stack.extend(operands.into_iter().rev().map(Frame::Enter));
+                        }
+                        Frame::Leave(expr, count) => {
+                            let operands = rewritten.split_off(rewritten.len() - count);
+                            rewritten.push(expr.with_operands(operands));
+                        }
+                    }
+                }
+                rewritten.pop().unwrap()
+            }
+        };
+        (expr, changed)
+    }
+
+    // Passes repeat until no rule applies, so rules must return None rather
+    // than an unchanged expression
+    pub fn rewrite(&self, expr: &Expr) -> Result<Expr, String> {
+        log::trace!("[rewrite] {expr:?}");
+        // Copied by folding, as a derived clone would recurse
+        let mut expr = expr.fold(&mut |expr| expr);
+        for _ in 0..MAX_PASSES {
+            match self.pass(expr) {
+                (rewritten, true) => expr = rewritten,
+                (rewritten, false) => return Ok(rewritten),
+            }
+        }
+        Err(format!("Rewriting exceeded {MAX_PASSES} passes"))
+    }
+}
+
+impl std::fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{:?} => {:?}", self.lhs, self.rhs))
+    }
+}
+
+fn is_metavariable(name: &str) -> bool {
+    name.starts_with('?')
+}
+
+fn metavariables(expr: &Expr) -> Set<String> {
+    expr.names()
+        .into_iter()
+        .filter(|name| is_metavariable(name))
+        .collect()
+}
+
+// The operands of an And or Or, with those of nested Ands within an And, or
+// Ors within an Or, in their place
+fn flatten(expr: &Expr) -> Set<&Expr> {
+    expr.operands()
+        .into_iter()
+        .flat_map(
+            |operand| match discriminant(operand) == discriminant(expr) {
+                true => flatten(operand),
+                false => Set::from([operand]),
+            },
+        )
+        .collect()
+}
+
+// Each pattern matched to a distinct operand of connective, with the operands
+// left over
+fn operand_matchings<'a>(
+    patterns: &[&Expr],
+    connective: &Expr,
+    exprs: &Set<&'a Expr>,
+    matching: &Matching,
+) -> Vec<(Matching, Set<&'a Expr>)> {
+    let mut operands = match patterns {
+        [] => return vec![(matching.clone(), exprs.clone())],
+        [pattern, rest @ ..] => exprs
+            .iter()
+            .flat_map(|expr| {
+                let mut remaining = exprs.clone();
+                remaining.remove(expr);
+                matchings(pattern, expr, matching)
+                    .into_iter()
+                    .flat_map(move |matching| {
+                        operand_matchings(rest, connective, &remaining, &matching)
+                    })
+            })
+            .collect::<Vec<_>>(),
+    };
+    // The last metavariable may also take every operand left, as by associativity
+    if let [Expr::Atom(name)] = patterns {
+        if is_metavariable(name) && !matching.contains_key(name) && exprs.len() > 1 {
+            let mut matching = matching.clone();
+            let exprs = exprs.iter().map(|&expr| expr.clone()).collect();
+            matching.insert(name.to_string(), connective.with_operands(exprs));
+            operands.push((matching, Set::new()));
+        }
+    }
+    operands
+}
+
+// All extensions of matching under which pattern is expr, with And and Or
+// matched modulo associativity and commutativity
+pub(crate) fn matchings(pattern: &Expr, expr: &Expr, matching: &Matching) -> Vec<Matching> {
+    match (pattern, expr) {
+        (Expr::Atom(name), _) if is_metavariable(name) => match matching.get(name) {
+            Some(bound) if bound == expr => vec![matching.clone()],
+            Some(_) => vec![],
+            None => {
+                let mut matching = matching.clone();
+                matching.insert(name.to_string(), expr.clone());
+                vec![matching]
+            }
+        },
+        (Expr::NotAtom(name), _) if is_metavariable(name) => {
+            matchings(&Expr::not(Expr::Atom(name.to_string())), expr, matching)
+        }
+        (Expr::Not(pattern), Expr::NotAtom(name)) => {
+            matchings(pattern, &Expr::Atom(name.to_string()), matching)
+        }
+        (Expr::And(_), Expr::And(_)) | (Expr::Or(_), Expr::Or(_)) => operand_matchings(
+            &flatten(pattern).into_iter().collect::<Vec<_>>(),
+            expr,
+            &flatten(expr),
+            matching,
+        )
+        .into_iter()
+        .filter(|(_, rest)| rest.is_empty())
+        .map(|(matching, _)| matching)
+        .collect(),
+        (Expr::AtMost(left, _), Expr::AtMost(right, _))
+        | (Expr::AtLeast(left, _), Expr::AtLeast(right, _))
+        | (Expr::Exactly(left, _), Expr::Exactly(right, _))
+            if left != right =>
+        {
+            vec![]
+        }
+        _ if discriminant(pattern) == discriminant(expr) && !pattern.is_literal() => {
+            let (patterns, exprs) = (pattern.operands(), expr.operands());
+            if patterns.len() != exprs.len() {
+                return vec![];
+            }
+            patterns.into_iter().zip(exprs).fold(
+                vec![matching.clone()],
+                |matchings_so_far, (pattern, expr)| {
+                    matchings_so_far
+                        .iter()
+                        .flat_map(|matching| matchings(pattern, expr, matching))
+                        .collect()
+                },
+            )
+        }
+        _ if pattern == expr => vec![matching.clone()],
+        _ => vec![],
+    }
+}
+
+impl Pattern {
+    // Accepted only if every instance is sound, that is if lhs = rhs is valid
+    // with its metavariables read as atoms
+    pub fn new(lhs: Expr, rhs: Expr) -> Result<Self, String> {
+        if let Some(name) = metavariables(&rhs).difference(&metavariables(&lhs)).next() {
+            return Err(format!(
+                "Metavariable {name} of {rhs:?} is not bound by {lhs:?}"
+            ));
+        }
+        if Expr::iff(lhs.clone(), rhs.clone())
+            .normal()
+            .coalesce()
+            .is_none()
+        {
+            return Err(format!("Unsound rewrite {lhs:?} => {rhs:?}"));
+        }
+        Ok(Pattern { lhs, rhs })
+    }
+
+    pub fn lhs(&self) -> &Expr {
+        &self.lhs
+    }
+
+    pub fn rhs(&self) -> &Expr {
+        &self.rhs
+    }
+}
+
+impl Rewrite for Pattern {
+    // An And or Or lhs may match only some of the operands of expr, with the
+    // rest kept alongside the rewritten rhs
+    fn rewrite(&self, expr: &Expr) -> Option<Expr> {
+        match (&self.lhs, expr) {
+            (Expr::And(_), Expr::And(_)) | (Expr::Or(_), Expr::Or(_)) => operand_matchings(
+                &flatten(&self.lhs).into_iter().collect::<Vec<_>>(),
+                expr,
+                &flatten(expr),
+                &Matching::new(),
+            )
+            .into_iter()
+            .next()
+            .map(|(matching, rest)| {
+                let rhs = self.rhs.substitute(&matching);
+                match rest.is_empty() {
+                    true => rhs,
+                    false => expr.with_operands(rest.into_iter().cloned().chain([rhs]).collect()),
+                }
+            }),
+            _ => matchings(&self.lhs, expr, &Matching::new())
+                .into_iter()
+                .next()
+                .map(|matching| self.rhs.substitute(&matching)),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    coalesceable::{map_proof, Coalesceable, Coalescence},
+    expression::Expr,
+    proof::Proof,
+    Dag, Set,
+};
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sequent {
+    pub antecedent: Vec<Expr>,
+    pub succedent: Vec<Expr>,
+}
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+pub struct SequentProof {
+    pub sequent: Sequent,
+    pub proof: Proof,
+}
+
+impl std::fmt::Debug for Sequent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let side = |exprs: &[Expr]| {
+            exprs
+                .iter()
+                .map(|expr| format!("{expr:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match (self.antecedent.as_slice(), self.succedent.as_slice()) {
+            ([], []) => f.write_str("|-"),
+            ([], succedent) => f.write_fmt(format_args!("|- {}", side(succedent))),
+            (antecedent, []) => f.write_fmt(format_args!("{} |-", side(antecedent))),
+            (antecedent, succedent) => {
+                f.write_fmt(format_args!("{} |- {}", side(antecedent), side(succedent)))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SequentProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{:?} [⊢]\n", self.sequent))?;
+        self.proof
+            .to_string()
+            .lines()
+            .try_for_each(|line| f.write_fmt(format_args!("  {line}\n")))
+    }
+}
+
+impl Coalesceable for Sequent {
+    fn axiom_set(&self) -> Set<Self> {
+        let expr = self.expr().normal();
+        expr.axiom_set().iter().map(self.lift()).collect()
+    }
+
+    fn children(&self) -> Set<Box<Self>> {
+        let lift = self.lift();
+        let expr = self.expr().normal();
+        expr.children()
+            .iter()
+            .map(|child| Box::new(lift(child)))
+            .collect()
+    }
+
+    fn dim_bound(&self) -> usize {
+        self.expr().normal().dim_bound()
+    }
+
+    fn spawn(&self, proof: &mut Dag<Set<Self>>) -> Set<Set<Self>> {
+        log::trace!("[spawn] {self:?}");
+        self.step(proof, |expr, proof| expr.spawn(proof))
+    }
+
+    fn fire(&self, proof: &mut Dag<Set<Self>>, tokens: &Set<Set<Self>>) -> Set<Set<Self>> {
+        log::trace!("[fire] {self:?} with {tokens:?}");
+        let tokens = self.lower_tokens(tokens);
+        self.step(proof, |expr, proof| expr.fire(proof, &tokens))
+    }
+
+    fn project(&self, proof: &mut Dag<Set<Self>>, tokens: &Set<Set<Self>>) -> Set<Set<Self>> {
+        log::trace!("[project] {self:?} with {tokens:?}");
+        let tokens = self.lower_tokens(tokens);
+        self.step(proof, |expr, proof| expr.project(proof, &tokens))
+    }
+
+    fn cite(&self, proof: &mut Dag<Set<Self>>, lemmas: &Set<Self>) -> Set<Set<Self>> {
+        log::trace!("[cite] {self:?} with {lemmas:?}");
+        let lemmas = lemmas.iter().map(|lemma| self.lower(lemma)).collect();
+        self.step(proof, |expr, proof| expr.cite(proof, &lemmas))
+    }
+
+    // Coalesces the formula directly, lifting its proof once rather than at
+    // every step
+    fn coalesce_with(&self, lemmas: &Set<Self>, dim_bound: usize) -> Option<Coalescence<Self>> {
+        log::trace!("[coalesce] {self:?} with {lemmas:?} within {dim_bound}");
+        let lemmas = lemmas.iter().map(|lemma| self.lower(lemma)).collect();
+        let (tokens, proof) = self.expr().normal().coalesce_with(&lemmas, dim_bound)?;
+        Some((self.lift_tokens(&tokens), map_proof(&proof, self.lift())))
+    }
+}
+
+impl Sequent {
+    pub fn new(antecedent: &[Expr], succedent: &[Expr]) -> Self {
+        Sequent {
+            antecedent: antecedent.to_vec(),
+            succedent: succedent.to_vec(),
+        }
+    }
+
+    pub fn converse(&self) -> Self {
+        Sequent {
+            antecedent: self.succedent.clone(),
+            succedent: self.antecedent.clone(),
+        }
+    }
+
+    pub fn expr(&self) -> Expr {
+        Expr::or(
+            &self
+                .antecedent
+                .iter()
+                .map(|expr| Expr::not(expr.clone()))
+                .chain(self.succedent.iter().cloned())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    // Tokens of a sequent hold sequents of a single formula, but for the token
+    // of its own formula, which holds the sequent itself
+    fn lift(&self) -> impl Fn(&Expr) -> Sequent + '_ {
+        let normal = self.expr().normal();
+        move |expr| {
+            if *expr == normal {
+                self.clone()
+            } else {
+                Sequent::new(&[], std::slice::from_ref(expr))
+            }
+        }
+    }
+
+    fn lower(&self, sequent: &Sequent) -> Expr {
+        match sequent.succedent.as_slice() {
+            [expr] if sequent != self && sequent.antecedent.is_empty() => expr.clone(),
+            _ => sequent.expr().normal(),
+        }
+    }
+
+    fn lift_tokens(&self, tokens: &Set<Set<Expr>>) -> Set<Set<Sequent>> {
+        let lift = self.lift();
+        tokens
+            .iter()
+            .map(|token| token.iter().map(&lift).collect())
+            .collect()
+    }
+
+    fn lower_tokens(&self, tokens: &Set<Set<Sequent>>) -> Set<Set<Expr>> {
+        tokens
+            .iter()
+            .map(|token| token.iter().map(|sequent| self.lower(sequent)).collect())
+            .collect()
+    }
+
+    // Takes one step of coalescence of the formula, over the proof lowered to it
+    fn step(
+        &self,
+        proof: &mut Dag<Set<Sequent>>,
+        step: impl FnOnce(&Expr, &mut Dag<Set<Expr>>) -> Set<Set<Expr>>,
+    ) -> Set<Set<Sequent>> {
+        let mut lowered = map_proof(proof, |sequent| self.lower(sequent));
+        let tokens = step(&self.expr().normal(), &mut lowered);
+        *proof = map_proof(&lowered, self.lift());
+        self.lift_tokens(&tokens)
+    }
+
+    pub fn prove(&self) -> Option<SequentProof> {
+        log::trace!("[prove] {self:?}");
+        let expr = self.expr().normal();
+        let (_, proof) = expr.coalesce()?;
+        Some(SequentProof {
+            sequent: self.clone(),
+            proof: Proof::reconstruct(&expr, &proof)?.relabel(&self.expr().originals()),
+        })
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{
+    coalesceable::Coalesceable,
+    expression::{Expr, MAX_TABLE_NAMES},
+    parseable::unreserved,
+    Map, Set,
+};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum SExpr {
+    Symbol(String),
+    List(Vec<SExpr>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Sat,
+    Unsat,
+    Unknown,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Script {
+    pub declarations: Set<String>,
+    pub assertions: Vec<Expr>,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Status::Sat => "sat",
+            Status::Unsat => "unsat",
+            Status::Unknown => "unknown",
+        })
+    }
+}
+
+fn read_sexprs(input: &str) -> Result<Vec<SExpr>, String> {
+    log::trace!("[read-sexprs] {input:?}");
+    let mut stack = vec![vec![]];
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start();
+        let Some(next) = rest.chars().next() else {
+            break;
+        };
+        let len = match next {
+            ';' => rest.find('\n').unwrap_or(rest.len()),
+            '(' => {
+                stack.push(vec![]);
+                1
+            }
+            ')' => {
+                let list = stack.pop().filter(|_| !stack.is_empty());
+                let list = list.ok_or(format!("Unmatched ) in {rest:?}"))?;
+                stack.last_mut().unwrap().push(SExpr::List(list));
+                1
+            }
+            '|' => {
+                let len = 1 + rest[1..]
+                    .find('|')
+                    .ok_or(format!("Unterminated symbol in {rest:?}"))?;
+                let symbol = SExpr::Symbol(rest[1..len].to_string());
+                stack.last_mut().unwrap().push(symbol);
+                len + 1
+            }
+            _ => {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || "();|".contains(c))
+                    .unwrap_or(rest.len());
+                let symbol = SExpr::Symbol(rest[..len].to_string());
+                stack.last_mut().unwrap().push(symbol);
+                len
+            }
+        };
+        rest = &rest[len..];
+    }
+    match stack.as_slice() {
+        [_] => Ok(stack.pop().unwrap()),
+        _ => Err("Unmatched ( at end of input".to_string()),
+    }
+}
+
+impl Script {
+    fn term(&self, sexpr: &SExpr, bindings: &Map<String, Expr>) -> Result<Expr, String> {
+        log::trace!("[term] {sexpr:?}");
+        match sexpr {
+            SExpr::Symbol(symbol) => match symbol.as_str() {
+                "true" => Ok(Expr::top()),
+                "false" => Ok(Expr::bottom()),
+                symbol if bindings.contains_key(symbol) => Ok(bindings[symbol].clone()),
+                symbol if self.declarations.contains(symbol) => Ok(Expr::Atom(symbol.to_string())),
+                symbol => Err(format!("Undeclared symbol {symbol}")),
+            },
+            SExpr::List(list) => {
+                let (operator, operands) = match list.as_slice() {
+                    [SExpr::Symbol(operator), operands @ ..] => (operator.as_str(), operands),
+                    _ => return Err(format!("Expected an application, got {sexpr:?}")),
+                };
+                if operator == "let" {
+                    return self.let_term(operands, bindings);
+                }
+                let terms = operands
+                    .iter()
+                    .map(|operand| self.term(operand, bindings))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let pairs = || {
+                    terms
+                        .windows(2)
+                        .map(|pair| (pair[0].clone(), pair[1].clone()))
+                };
+                match (operator, terms.as_slice()) {
+                    ("not", [term]) => Ok(Expr::not(term.clone())),
+                    ("and", _) => Ok(Expr::and(&terms)),
+                    ("or", _) => Ok(Expr::or(&terms)),
+                    // Right associative
+                    ("=>", [.., _, _]) => Ok(terms[..terms.len() - 1]
+                        .iter()
+                        .rev()
+                        .fold(terms[terms.len() - 1].clone(), |right, left| {
+                            Expr::implies(left.clone(), right)
+                        })),
+                    // Left associative
+                    ("xor", [first, _, ..]) => Ok(terms[1..]
+                        .iter()
+                        .fold(first.clone(), |left, right| Expr::xor(left, right.clone()))),
+                    // Chainable
+                    ("=", [_, _, ..]) => Ok(Expr::and(
+                        &pairs()
+                            .map(|(left, right)| Expr::iff(left, right))
+                            .collect::<Vec<_>>(),
+                    )),
+                    ("distinct", [left, right]) => Ok(Expr::xor(left.clone(), right.clone())),
+                    // Booleans take only two values, so any three are never distinct
+                    ("distinct", [_, _, _, ..]) => Ok(Expr::bottom()),
+                    ("ite", [condition, then, otherwise]) => Ok(Expr::or(&[
+                        Expr::and(&[condition.clone(), then.clone()]),
+                        Expr::and(&[Expr::not(condition.clone()), otherwise.clone()]),
+                    ])),
+                    (operator, terms) => Err(format!(
+                        "Unsupported {operator} of {} operands in {sexpr:?}",
+                        terms.len()
+                    )),
+                }
+            }
+        }
+    }
+
+    fn let_term(&self, operands: &[SExpr], bindings: &Map<String, Expr>) -> Result<Expr, String> {
+        let (pairs, body) = match operands {
+            [SExpr::List(pairs), body] => (pairs, body),
+            _ => return Err(format!("Expected (let ((x t) ..) body), got {operands:?}")),
+        };
+        // Bindings are parallel, so each term sees only the outer bindings
+        let mut inner = bindings.clone();
+        for pair in pairs {
+            match pair {
+                SExpr::List(pair) => match pair.as_slice() {
+                    [SExpr::Symbol(name), term] => {
+                        inner.insert(name.to_string(), self.term(term, bindings)?);
+                    }
+                    _ => return Err(format!("Expected (x t) binding, got {pair:?}")),
+                },
+                _ => return Err(format!("Expected (x t) binding, got {pair:?}")),
+            }
+        }
+        self.term(body, &inner)
+    }
+
+    fn command(&mut self, sexpr: &SExpr) -> Result<(), String> {
+        log::trace!("[command] {sexpr:?}");
+        let list = match sexpr {
+            SExpr::List(list) => list.as_slice(),
+            _ => return Err(format!("Expected a command, got {sexpr:?}")),
+        };
+        let symbol = |sexpr: &SExpr| match sexpr {
+            SExpr::Symbol(symbol) => Some(symbol.to_owned()),
+            _ => None,
+        };
+        match list {
+            [SExpr::Symbol(command), ..]
+                if matches!(
+                    command.as_str(),
+                    "set-logic" | "set-info" | "set-option" | "get-model" | "get-info" | "exit"
+                ) => {}
+            [SExpr::Symbol(command), name, sort]
+                if command == "declare-const" && symbol(sort).as_deref() == Some("Bool") =>
+            {
+                let name = symbol(name).ok_or(format!("Expected a symbol, got {name:?}"))?;
+                self.declarations.insert(unreserved(name)?);
+            }
+            [SExpr::Symbol(command), name, SExpr::List(arguments), sort]
+                if command == "declare-fun"
+                    && arguments.is_empty()
+                    && symbol(sort).as_deref() == Some("Bool") =>
+            {
+                let name = symbol(name).ok_or(format!("Expected a symbol, got {name:?}"))?;
+                self.declarations.insert(unreserved(name)?);
+            }
+            [SExpr::Symbol(command), term] if command == "assert" => {
+                let expr = self.term(term, &Map::new())?;
+                self.assertions.push(expr);
+            }
+            [SExpr::Symbol(command)] if command == "check-sat" => {}
+            _ => return Err(format!("Unsupported command {sexpr:?}")),
+        }
+        Ok(())
+    }
+
+    // Commands after the first (check-sat) are ignored
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut script = Script::default();
+        for sexpr in read_sexprs(input)? {
+            let check_sat = sexpr == SExpr::List(vec![SExpr::Symbol("check-sat".to_string())]);
+            script.command(&sexpr)?;
+            if check_sat {
+                break;
+            }
+        }
+        Ok(script)
+    }
+
+    // Valid exactly when the assertions are unsatisfiable
+    pub fn query(&self) -> Expr {
+        Expr::not(Expr::and(&self.assertions))
+    }
+
+    // Sat only given a model of the assertions, as coalescence failing to prove
+    // the query is no proof of satisfiability
+    pub fn check_sat(&self) -> Status {
+        log::trace!("[check-sat] {self:?}");
+        if self.query().normal().coalesce().is_some() {
+            return Status::Unsat;
+        }
+
+        // Search the truth table for a model, so long as there are few enough names
+        let assertions = Expr::and(&self.assertions);
+        let names = assertions.names().into_iter().collect::<Vec<_>>();
+        if names.len() > MAX_TABLE_NAMES {
+            log::debug!("[check-sat] {} names are too many to tabulate", names.len());
+            return Status::Unknown;
+        }
+        match (0..1u32 << names.len()).any(|row| {
+            let assignment = names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.to_owned(), row & (1 << index) != 0))
+                .collect::<Map<_, _>>();
+            assertions.evaluate(&assignment) == Ok(true)
+        }) {
+            true => Status::Sat,
+            false => Status::Unsat,
+        }
+    }
+}
+
+fn smtlib_symbol(name: &str) -> String {
+    let simple = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c));
+    match simple {
+        true => name.to_string(),
+        false => format!("|{name}|"),
+    }
+}
+
+impl Expr {
+    pub fn to_smtlib(&self) -> String {
+        match self {
+            Expr::And(exprs) if exprs.is_empty() => "true".to_string(),
+            Expr::Or(exprs) if exprs.is_empty() => "false".to_string(),
+            Expr::And(exprs) | Expr::Or(exprs) if exprs.len() == 1 => {
+                exprs.first().unwrap().to_smtlib()
+            }
+            Expr::And(exprs) | Expr::Or(exprs) => format!(
+                "({} {})",
+                match self {
+                    Expr::And(_) => "and",
+                    _ => "or",
+                },
+                exprs
+                    .iter()
+                    .map(|expr| expr.to_smtlib())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            Expr::Not(expr) => format!("(not {})", expr.to_smtlib()),
+            Expr::Implies(left, right) => {
+                format!("(=> {} {})", left.to_smtlib(), right.to_smtlib())
+            }
+            Expr::Iff(left, right) => format!("(= {} {})", left.to_smtlib(), right.to_smtlib()),
+            Expr::Xor(left, right) => format!("(xor {} {})", left.to_smtlib(), right.to_smtlib()),
+            // The Boolean theory has no cardinality constraints
+            Expr::AtMost(..) | Expr::AtLeast(..) | Expr::Exactly(..) => self.normal().to_smtlib(),
+            Expr::Atom(name) => smtlib_symbol(name),
+            Expr::NotAtom(name) => format!("(not {})", smtlib_symbol(name)),
+        }
+    }
+
+    // A script that is unsat exactly when the expression is valid
+    pub fn to_smtlib_validity(&self) -> String {
+        self.names()
+            .iter()
+            .map(|name| format!("(declare-const {} Bool)", smtlib_symbol(name)))
+            .chain([
+                format!("(assert (not {}))", self.to_smtlib()),
+                "(check-sat)".to_string(),
+            ])
+            .map(|line| line + "\n")
+            .collect()
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{
+    expression::Expr,
+    proof::{Proof, Rule},
+    sequent::{Sequent, SequentProof},
+    Set,
+};
+
+// bussproofs has no inference macro beyond \QuinaryInfC
+const MAX_PREMISES: usize = 5;
+
+pub trait Texable {
+    fn tex(&self) -> String;
+}
+
+fn tex_name(name: &str) -> String {
+    let escaped = name
+        .chars()
+        .map(|c| match c {
+            '_' | '#' | '$' | '%' | '&' | '{' | '}' => format!("\\{c}"),
+            '\\' => "\\textbackslash{}".to_string(),
+            '^' | '~' => format!("\\{c}{{}}"),
+            _ => c.to_string(),
+        })
+        .collect::<String>();
+    if name.chars().count() == 1 {
+        escaped
+    } else {
+        format!("\\mathit{{{escaped}}}")
+    }
+}
+
+impl Texable for Expr {
+    fn tex(&self) -> String {
+        log::trace!("[tex] {self:?}");
+        let operand = |expr: &Expr| match expr {
+            Self::And(exprs) | Self::Or(exprs) if !exprs.is_empty() => format!("({})", expr.tex()),
+            Self::Implies(..) | Self::Iff(..) | Self::Xor(..) => format!("({})", expr.tex()),
+            _ => expr.tex(),
+        };
+        match self {
+            Self::And(exprs) if exprs.is_empty() => "\\top".to_string(),
+            Self::Or(exprs) if exprs.is_empty() => "\\bot".to_string(),
+            Self::And(exprs) | Self::Or(exprs) => exprs
+                .iter()
+                .map(|expr| operand(expr))
+                .collect::<Vec<_>>()
+                .join(match self {
+                    Self::And(_) => " \\land ",
+                    Self::Or(_) => " \\lor ",
+                    _ => panic!("Impossible match arm"),
+                }),
+            Self::Not(expr) => match **expr {
+                Self::Atom(_) | Self::NotAtom(_) | Self::Not(_) => format!("\\neg {}", expr.tex()),
+                _ => format!("\\neg ({})", expr.tex()),
+            },
+            Self::Implies(left, right) => format!("{} \\to {}", operand(left), operand(right)),
+            Self::Iff(left, right) => {
+                format!("{} \\leftrightarrow {}", operand(left), operand(right))
+            }
+            Self::Xor(left, right) => format!("{} \\oplus {}", operand(left), operand(right)),
+            Self::AtMost(count, exprs)
+            | Self::AtLeast(count, exprs)
+            | Self::Exactly(count, exprs) => {
+                format!(
+                    "{}_{{{count}}}({})",
+                    match self {
+                        Self::AtMost(..) => "\\mathrm{AtMost}",
+                        Self::AtLeast(..) => "\\mathrm{AtLeast}",
+                        _ => "\\mathrm{Exactly}",
+                    },
+                    exprs.iter().map(Expr::tex).collect::<Vec<_>>().join(", ")
+                )
+            }
+            Self::Atom(name) => tex_name(name),
+            Self::NotAtom(name) => format!("\\neg {}", tex_name(name)),
+        }
+    }
+}
+
+impl Texable for Set<Expr> {
+    fn tex(&self) -> String {
+        log::trace!("[tex] {self:?}");
+        match self.is_empty() {
+            true => "\\vdash".to_string(),
+            false => format!(
+                "\\vdash {}",
+                self.iter().map(Expr::tex).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+}
+
+impl Texable for Rule {
+    fn tex(&self) -> String {
+        match self {
+            Rule::Axiom => "\\mathrm{Ax}",
+            Rule::Lemma(_) => "\\mathrm{Lem}",
+            Rule::And(_) => "\\land",
+            Rule::Or(_) => "\\lor",
+            Rule::Project(_) => "\\mathrm{W}",
+        }
+        .to_string()
+    }
+}
+
+fn inference(
+    rule: &Rule,
+    conclusion: &Set<Expr>,
+    premises: Vec<(Set<Expr>, Vec<String>)>,
+) -> Vec<String> {
+    log::trace!("[inference] {conclusion:?} by {rule:?}");
+    if premises.len() > MAX_PREMISES {
+        // Too wide for bussproofs, so conjoin the premises a chunk at a time
+        let context = match rule {
+            Rule::And(expr) => {
+                let mut partial = conclusion.to_owned();
+                partial.remove(expr);
+                partial
+            }
+            _ => panic!("Only conjunction has more than one premise, but got {rule:?}"),
+        };
+        let chunked = premises
+            .chunks(MAX_PREMISES)
+            .map(|chunk| match chunk {
+                [premise] => premise.to_owned(),
+                _ => {
+                    let intermediate = context
+                        .iter()
+                        .cloned()
+                        .chain([Expr::and(
+                            &chunk
+                                .iter()
+                                .flat_map(|(token, _)| token.difference(&context).cloned())
+                                .collect::<Vec<_>>(),
+                        )])
+                        .collect::<Set<_>>();
+                    let lines = inference(rule, &intermediate, chunk.to_vec());
+                    (intermediate, lines)
+                }
+            })
+            .collect();
+        return inference(rule, conclusion, chunked);
+    }
+
+    let infer = match premises.len() {
+        1 => "UnaryInfC",
+        2 => "BinaryInfC",
+        3 => "TrinaryInfC",
+        4 => "QuaternaryInfC",
+        5 => "QuinaryInfC",
+        _ => panic!("Expected between 1 and {MAX_PREMISES} premises for {rule:?}"),
+    };
+    premises
+        .into_iter()
+        .flat_map(|(_, lines)| lines)
+        .chain([
+            format!("\\RightLabel{{${}$}}", rule.tex()),
+            format!("\\{infer}{{${}$}}", conclusion.tex()),
+        ])
+        .collect()
+}
+
+fn prooftree(proof: &Proof) -> Vec<String> {
+    match &proof.rule {
+        Rule::Axiom => vec![format!("\\AxiomC{{${}$}}", proof.token.tex())],
+        // Cited lemmas and the empty conjunction are inferences with no premises
+        Rule::Lemma(_) | Rule::And(_) if proof.premises.is_empty() => vec![
+            "\\AxiomC{}".to_string(),
+            format!("\\RightLabel{{${}$}}", proof.rule.tex()),
+            format!("\\UnaryInfC{{${}$}}", proof.token.tex()),
+        ],
+        rule => inference(
+            rule,
+            &proof.token,
+            proof
+                .premises
+                .iter()
+                .map(|premise| (premise.token.to_owned(), prooftree(premise)))
+                .collect(),
+        ),
+    }
+}
+
+fn environment(lines: Vec<String>) -> String {
+    ["\\begin{prooftree}".to_string()]
+        .into_iter()
+        .chain(lines)
+        .chain(["\\end{prooftree}".to_string()])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Texable for Proof {
+    fn tex(&self) -> String {
+        log::trace!("[tex] {self:?}");
+        environment(prooftree(self))
+    }
+}
+
+impl Texable for Sequent {
+    fn tex(&self) -> String {
+        log::trace!("[tex] {self:?}");
+        let side = |exprs: &[Expr]| exprs.iter().map(Expr::tex).collect::<Vec<_>>().join(", ");
+        [
+            side(&self.antecedent),
+            "\\vdash".to_string(),
+            side(&self.succedent),
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+// The one-sided proof of ¬Γ ∨ Δ, concluding with the original Γ ⊢ Δ
+impl Texable for SequentProof {
+    fn tex(&self) -> String {
+        log::trace!("[tex] {self:?}");
+        environment(
+            prooftree(&self.proof)
+                .into_iter()
+                .chain([
+                    "\\RightLabel{$\\mathrm{Seq}$}".to_string(),
+                    format!("\\UnaryInfC{{${}$}}", self.sequent.tex()),
+                ])
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{
+    entailment::entails,
+    expression::Expr,
+    sequent::{Sequent, SequentProof},
+    Map,
+};
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Theory {
+    pub axioms: Map<String, Expr>,
+}
+
+#[derive(Ord, Eq, PartialOrd, PartialEq, Clone, Debug)]
+pub struct TheoryProof {
+    pub used: Vec<String>,
+    pub proof: SequentProof,
+}
+
+impl FromIterator<(String, Expr)> for Theory {
+    fn from_iter<I: IntoIterator<Item = (String, Expr)>>(axioms: I) -> Self {
+        Theory {
+            axioms: axioms.into_iter().collect(),
+        }
+    }
+}
+
+impl Theory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: &str, axiom: Expr) -> Option<Expr> {
+        self.axioms.insert(name.to_string(), axiom)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Expr> {
+        self.axioms.remove(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axioms.is_empty()
+    }
+
+    // Every axiom as a hypothesis of the goal, Γ ⊢ goal
+    pub fn sequent(&self, goal: &Expr) -> Sequent {
+        Sequent::new(
+            &self.axioms.values().cloned().collect::<Vec<_>>(),
+            std::slice::from_ref(goal),
+        )
+    }
+
+    pub fn prove(&self, goal: &Expr) -> Option<TheoryProof> {
+        log::trace!("[prove] {goal:?} in {self:?}");
+        let names = self.axioms.keys().collect::<Vec<_>>();
+        let axioms = self.axioms.values().cloned().collect::<Vec<_>>();
+        let entailment = entails(&axioms, goal)?;
+        Some(TheoryProof {
+            used: entailment
+                .core
+                .iter()
+                .map(|&index| names[index].to_owned())
+                .collect(),
+            proof: entailment.proof,
+        })
+    }
+}
@@ -0,0 +1,320 @@
+use std::{fs, path::Path};
+
+use crate::{
+    expression::Expr,
+    parseable::unreserved,
+    theory::{Theory, TheoryProof},
+    Set,
+};
+
+// Longest first, so that each is matched before its prefixes
+const PUNCTUATION: [&str; 16] = [
+    "<~>", "<=>", "=>", "<=", "~|", "~&", "|", "&", "~", "(", ")", "[", "]", ",", ".", ":",
+];
+
+// Includes nested deeper than this are assumed to be cyclic
+const MAX_INCLUDE_DEPTH: usize = 32;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Defined(String),
+    Punctuation(&'static str),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Problem {
+    pub theory: Theory,
+    pub conjectures: Vec<(String, Expr)>,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    log::trace!("[tokenize] {input:?}");
+    let mut tokens = vec![];
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start();
+        let Some(next) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        let (token, len) = match next {
+            '%' => (None, rest.find('\n').unwrap_or(rest.len())),
+            '/' if rest.starts_with("/*") => (
+                None,
+                2 + rest[2..]
+                    .find("*/")
+                    .ok_or("Unterminated comment".to_string())?
+                    + 2,
+            ),
+            '\'' | '"' => {
+                let len = 1 + rest[1..]
+                    .find(next)
+                    .ok_or(format!("Unterminated quote in {rest:?}"))?;
+                (Some(Token::Quoted(rest[1..len].to_string())), len + 1)
+            }
+            '$' | '_' | 'a'..='z' | 'A'..='Z' | '0'..='9' => {
+                let len = 1 + rest[1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len() - 1);
+                let word = rest[..len].to_string();
+                match next {
+                    '$' => (Some(Token::Defined(word)), len),
+                    _ => (Some(Token::Word(word)), len),
+                }
+            }
+            _ => {
+                let punctuation = PUNCTUATION
+                    .iter()
+                    .find(|&&punctuation| rest.starts_with(punctuation))
+                    .ok_or(format!("Unexpected {next:?} in {rest:?}"))?;
+                (Some(Token::Punctuation(punctuation)), punctuation.len())
+            }
+        };
+        tokens.extend(token);
+        rest = &rest[len..];
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or("Expected a token, got end of input".to_string())?;
+        self.index += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, punctuation: &str) -> Result<(), String> {
+        match self.next()? {
+            Token::Punctuation(found) if found == punctuation => Ok(()),
+            token => Err(format!("Expected {punctuation:?}, got {token:?}")),
+        }
+    }
+
+    fn accept(&mut self, punctuation: &str) -> bool {
+        match self.peek() {
+            Some(Token::Punctuation(found)) if *found == punctuation => {
+                self.index += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Word(name) | Token::Quoted(name) => Ok(name),
+            token => Err(format!("Expected a name, got {token:?}")),
+        }
+    }
+
+    // Binary connectives other than | and & do not associate, so
+    // mixing connectives requires parentheses
+    fn formula(&mut self) -> Result<Expr, String> {
+        let left = self.unary()?;
+        let connective = match self.peek() {
+            Some(Token::Punctuation(
+                connective @ ("|" | "&" | "=>" | "<=" | "<=>" | "<~>" | "~|" | "~&"),
+            )) => *connective,
+            _ => return Ok(left),
+        };
+        self.index += 1;
+        let right = self.unary()?;
+        match connective {
+            "|" | "&" => {
+                let mut operands = vec![left, right];
+                while self.accept(connective) {
+                    operands.push(self.unary()?);
+                }
+                Ok(match connective {
+                    "|" => Expr::or(&operands),
+                    _ => Expr::and(&operands),
+                })
+            }
+            "=>" => Ok(Expr::implies(left, right)),
+            "<=" => Ok(Expr::implies(right, left)),
+            "<=>" => Ok(Expr::iff(left, right)),
+            "<~>" => Ok(Expr::xor(left, right)),
+            "~|" => Ok(Expr::not(Expr::or(&[left, right]))),
+            _ => Ok(Expr::not(Expr::and(&[left, right]))),
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next()? {
+            Token::Punctuation("~") => Ok(Expr::not(self.unary()?)),
+            Token::Punctuation("(") => {
+                let expr = self.formula()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Defined(word) if word == "$true" => Ok(Expr::top()),
+            Token::Defined(word) if word == "$false" => Ok(Expr::bottom()),
+            Token::Word(name) if name.starts_with(|c: char| c.is_ascii_uppercase()) => Err(
+                format!("Expected a propositional atom, got variable {name}"),
+            ),
+            Token::Word(name) | Token::Quoted(name) => match self.peek() {
+                Some(Token::Punctuation("(")) => Err(format!(
+                    "Expected a propositional atom, got predicate {name}"
+                )),
+                _ => unreserved(name).map(Expr::Atom),
+            },
+            token => Err(format!("Expected a propositional formula, got {token:?}")),
+        }
+    }
+
+    fn selection(&mut self) -> Result<Option<Set<String>>, String> {
+        if !self.accept(",") {
+            return Ok(None);
+        }
+        self.expect("[")?;
+        let mut names = Set::new();
+        if !self.accept("]") {
+            loop {
+                names.insert(self.name()?);
+                if self.accept("]") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        Ok(Some(names))
+    }
+}
+
+impl Problem {
+    // Names must be unique across the problem and every file it includes
+    fn add(&mut self, name: String, role: &str, expr: Expr) -> Result<(), String> {
+        if self.theory.axioms.contains_key(&name)
+            || self.conjectures.iter().any(|(other, _)| *other == name)
+        {
+            return Err(format!("Duplicate formula name {name}"));
+        }
+        match role {
+            "axiom" | "hypothesis" | "definition" | "assumption" | "lemma" | "theorem"
+            | "corollary" | "plain" | "negated_conjecture" => {
+                self.theory.insert(&name, expr);
+                Ok(())
+            }
+            "conjecture" => {
+                self.conjectures.push((name, expr));
+                Ok(())
+            }
+            role => Err(format!("Unsupported role {role} of {name}")),
+        }
+    }
+
+    fn read(
+        &mut self,
+        input: &str,
+        directory: &Path,
+        selection: Option<&Set<String>>,
+        depth: usize,
+    ) -> Result<(), String> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            index: 0,
+        };
+        while parser.peek().is_some() {
+            match parser.name()?.as_str() {
+                "fof" | "cnf" => {
+                    parser.expect("(")?;
+                    let name = parser.name()?;
+                    parser.expect(",")?;
+                    let role = parser.name()?;
+                    parser.expect(",")?;
+                    let expr = parser.formula()?;
+                    // Ignore any source and useful info annotations
+                    let mut nesting = 1;
+                    while nesting > 0 {
+                        match parser.next()? {
+                            Token::Punctuation("(" | "[") => nesting += 1,
+                            Token::Punctuation(")" | "]") => nesting -= 1,
+                            _ => (),
+                        }
+                    }
+                    parser.expect(".")?;
+                    if !matches!(selection, Some(selection) if !selection.contains(&name)) {
+                        self.add(name, &role, expr)?;
+                    }
+                }
+                "include" => {
+                    parser.expect("(")?;
+                    let file = parser.name()?;
+                    let included = parser.selection()?;
+                    parser.expect(")")?;
+                    parser.expect(".")?;
+                    self.include(
+                        &directory.join(file),
+                        directory,
+                        included.as_ref(),
+                        depth + 1,
+                    )?;
+                }
+                statement => return Err(format!("Unsupported statement {statement}")),
+            }
+        }
+        Ok(())
+    }
+
+    fn include(
+        &mut self,
+        path: &Path,
+        directory: &Path,
+        selection: Option<&Set<String>>,
+        depth: usize,
+    ) -> Result<(), String> {
+        log::trace!("[include] {}", path.display());
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(format!("{}: Includes nested too deeply", path.display()));
+        }
+        let input = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        self.read(&input, directory, selection, depth)
+            .map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    // Includes are resolved relative to directory, as TPTP resolves them
+    // relative to its root
+    pub fn parse(input: &str, directory: &Path) -> Result<Self, String> {
+        let mut problem = Problem::default();
+        problem.read(input, directory, None, 0)?;
+        Ok(problem)
+    }
+
+    pub fn load(path: &Path, directory: &Path) -> Result<Self, String> {
+        let mut problem = Problem::default();
+        problem.include(path, directory, None, 0)?;
+        Ok(problem)
+    }
+
+    // The conjunction of the conjectures, or else false, so that a problem
+    // without any is proven by showing its formulas are unsatisfiable
+    pub fn goal(&self) -> Expr {
+        match self.conjectures.as_slice() {
+            [] => Expr::bottom(),
+            [(_, conjecture)] => conjecture.clone(),
+            conjectures => Expr::and(
+                &conjectures
+                    .iter()
+                    .map(|(_, conjecture)| conjecture.clone())
+                    .collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    pub fn prove(&self) -> Option<TheoryProof> {
+        log::trace!("[prove] {self:?}");
+        self.theory.prove(&self.goal())
+    }
+}
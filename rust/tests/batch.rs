@@ -0,0 +1,137 @@
+use std::{env, fs};
+
+use coalescence::{
+    batch::{Batch, Outcome, Query},
+    expression::Expr,
+    parseable::Parseable,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+const INPUT: &str = "
+// Contraposition
+let forward = a > b;
+let backward = ~b > ~a;
+equiv forward, backward;
+
+prove forward | ~forward; // excluded middle
+prove a > b;
+entails forward,
+        c,
+        a |- b;
+equiv a ^ b, a = b;
+";
+
+#[test]
+fn parse_batch() -> Result<(), String> {
+    log_init();
+
+    let batch = Batch::parse(INPUT)?;
+    assert_eq!(
+        batch.queries,
+        vec![
+            (
+                5,
+                Query::Equiv(Expr::parse("a > b")?, Expr::parse("~b > ~a")?)
+            ),
+            (7, Query::Prove(Expr::parse("(a > b) | ~(a > b)")?)),
+            (8, Query::Prove(Expr::parse("a > b")?)),
+            (
+                9,
+                Query::Entails(
+                    vec![Expr::parse("a > b")?, Expr::parse("c")?, Expr::parse("a")?],
+                    Expr::parse("b")?
+                )
+            ),
+            (
+                12,
+                Query::Equiv(Expr::parse("a ^ b")?, Expr::parse("a = b")?)
+            ),
+        ]
+    );
+    assert_eq!(batch.queries[3].1.to_string(), "entails a > b, c, a |- b");
+
+    Ok(())
+}
+
+#[test]
+fn batch_cardinality() -> Result<(), String> {
+    log_init();
+
+    let batch = Batch::parse("prove atmost(1; a, b) | (a & b);\nprove atleast(2;\n  a, b);")?;
+    assert_eq!(
+        batch.queries,
+        vec![
+            (1, Query::Prove(Expr::parse("atmost(1; a, b) | (a & b)")?)),
+            (2, Query::Prove(Expr::parse("atleast(2; a, b)")?)),
+        ]
+    );
+    let outcomes = batch
+        .run()
+        .into_iter()
+        .map(|report| report.outcome)
+        .collect::<Vec<_>>();
+    assert_eq!(outcomes[0], Outcome::Proven);
+    assert!(!outcomes[1].holds());
+
+    Ok(())
+}
+
+#[test]
+fn parse_batch_errors() -> Result<(), String> {
+    log_init();
+
+    let err = Batch::parse("prove a;\n\nfrobnicate a;").unwrap_err();
+    assert_eq!(err, "line 3: Unsupported statement frobnicate");
+    let err = Batch::parse("prove a;\nprove b").unwrap_err();
+    assert!(err.starts_with("line 2: Expected ;"), "{err}");
+    assert!(Batch::parse("equiv a;").is_err());
+    assert!(Batch::parse("entails a, b;").is_err());
+    assert!(Batch::parse("let a b = c;").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn run_batch() -> Result<(), String> {
+    log_init();
+
+    let reports = Batch::parse(INPUT)?.run();
+    let outcomes = reports
+        .iter()
+        .map(|report| report.outcome.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(outcomes[0], Outcome::Proven);
+    assert_eq!(outcomes[1], Outcome::Proven);
+    assert_eq!(outcomes[2], Outcome::Unproven);
+    assert_eq!(outcomes[3], Outcome::Entailed(vec![0, 2]));
+    assert!(matches!(outcomes[4], Outcome::Counterexample(_)));
+    assert_eq!(
+        outcomes.iter().map(Outcome::holds).collect::<Vec<_>>(),
+        vec![true, true, false, true, false]
+    );
+    assert!(reports[0]
+        .to_string()
+        .starts_with("equiv a > b, ~(b) > ~(a): proven ("));
+
+    Ok(())
+}
+
+#[test]
+fn load_batch() -> Result<(), String> {
+    log_init();
+
+    let path = env::temp_dir().join(format!("coalescence-batch-{}", std::process::id()));
+    fs::write(&path, INPUT).map_err(|err| err.to_string())?;
+    let batch = Batch::load(&path);
+    let _ = fs::remove_file(&path);
+    assert_eq!(batch?, Batch::parse(INPUT)?);
+
+    assert!(Batch::load(&path).is_err());
+
+    Ok(())
+}
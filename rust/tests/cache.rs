@@ -0,0 +1,99 @@
+#![cfg(feature = "cache")]
+
+use std::{env, fs, path::PathBuf, thread};
+
+use coalescence::{
+    cache::{CacheEntry, ProofCache},
+    expression::Expr,
+    parseable::Parseable,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+fn directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("coalescence-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    directory
+}
+
+#[test]
+fn cache_proofs() -> Result<(), String> {
+    log_init();
+
+    let directory = directory("cache-proofs");
+    let cache = ProofCache::open(&directory)?;
+    let valid = Expr::parse("a > a")?;
+    let invalid = Expr::parse("a > b")?;
+
+    assert_eq!(cache.get(&valid), None);
+    let entry = cache.prove(&valid)?;
+    assert!(entry.valid);
+    assert!(entry.proof.is_some());
+    assert_eq!(cache.get(&valid), Some(entry));
+    assert_eq!(cache.get(&Expr::parse("a | ~a")?), cache.get(&valid));
+
+    let entry = cache.prove(&invalid)?;
+    assert!(!entry.valid);
+    assert_eq!(entry.proof, None);
+    assert_eq!(cache.get(&invalid), Some(entry));
+
+    let _ = fs::remove_dir_all(&directory);
+    Ok(())
+}
+
+#[test]
+fn cache_invalidated() -> Result<(), String> {
+    log_init();
+
+    let directory = directory("cache-invalidated");
+    let cache = ProofCache::open(&directory)?;
+    let expr = Expr::parse("a > a")?;
+    cache.prove(&expr)?;
+
+    let path = cache.path(&expr);
+    let json = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    let mut entry = serde_json::from_str::<CacheEntry>(&json).map_err(|err| err.to_string())?;
+    entry.version = "0.0.0".to_string();
+    fs::write(
+        &path,
+        serde_json::to_string(&entry).map_err(|err| err.to_string())?,
+    )
+    .map_err(|err| err.to_string())?;
+
+    assert_eq!(cache.get(&expr), None);
+    assert!(cache.prove(&expr)?.valid);
+    assert!(cache.get(&expr).is_some());
+
+    let _ = fs::remove_dir_all(&directory);
+    Ok(())
+}
+
+#[test]
+fn cache_concurrent_writers() -> Result<(), String> {
+    log_init();
+
+    let directory = directory("cache-concurrent-writers");
+    let cache = ProofCache::open(&directory)?;
+    let expr = Expr::parse("(a > b) & (b > c) > (a > c)")?;
+    let entry = cache.prove(&expr)?;
+
+    thread::scope(|scope| {
+        let writers = (0..16)
+            .map(|_| scope.spawn(|| (0..64).try_for_each(|_| cache.insert(&entry))))
+            .collect::<Vec<_>>();
+        writers
+            .into_iter()
+            .try_for_each(|writer| writer.join().map_err(|_| "Writer panicked".to_string())?)
+    })?;
+    assert_eq!(cache.get(&expr), Some(entry));
+    // Every partial entry was renamed into place
+    let files = fs::read_dir(&directory).map_err(|err| err.to_string())?;
+    assert_eq!(files.count(), 1);
+
+    let _ = fs::remove_dir_all(&directory);
+    Ok(())
+}
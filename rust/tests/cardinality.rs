@@ -0,0 +1,193 @@
+use coalescence::{
+    cardinality::Cardinality, coalesceable::Coalesceable, expression::Expr, parseable::Parseable,
+    Map,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+fn atoms(count: usize) -> Vec<Expr> {
+    (0..count)
+        .map(|index| Expr::Atom(format!("x{index}")))
+        .collect()
+}
+
+// Compares by truth table, as each constraint evaluates without encoding
+fn equivalent(left: &Expr, right: &Expr, count: usize) -> bool {
+    (0..1u32 << count).all(|row| {
+        let assignment = (0..count)
+            .map(|index| (format!("x{index}"), row & (1 << index) != 0))
+            .collect::<Map<_, _>>();
+        left.evaluate(&assignment) == right.evaluate(&assignment)
+    })
+}
+
+#[test]
+fn parse_cardinality() -> Result<(), String> {
+    log_init();
+
+    let exprs = atoms(3);
+    assert_eq!(
+        Expr::parse("atmost(1; x0, x1, x2)")?,
+        Expr::at_most(1, &exprs)
+    );
+    assert_eq!(
+        Expr::parse("atleast(2; x0, x1, x2) & a")?,
+        Expr::and(&[Expr::at_least(2, &exprs), Expr::Atom("a".to_string())])
+    );
+    assert_eq!(
+        Expr::parse("~exactly(1; x0 & x1, (x2 | a))")?,
+        Expr::not(Expr::exactly(
+            1,
+            &[Expr::parse("x0 & x1")?, Expr::parse("x2 | a")?]
+        ))
+    );
+    assert_eq!(
+        format!("{:?}", Expr::parse("exactly(2; x0, ~x1, x2 > x0)")?),
+        "exactly(2; x0, ~(x1), x2 > x0)"
+    );
+
+    assert!(Expr::parse("atmost(x0, x1)").is_err());
+    assert!(Expr::parse("atmost(-1; x0, x1)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn encodings_equivalent() -> Result<(), String> {
+    log_init();
+
+    for count in 0..=4 {
+        let exprs = atoms(count);
+        for bound in 0..=count + 1 {
+            for constraint in [
+                Expr::at_most(bound, &exprs),
+                Expr::at_least(bound, &exprs),
+                Expr::exactly(bound, &exprs),
+            ] {
+                for expr in [constraint.clone(), Expr::not(constraint.clone())] {
+                    for cardinality in [Cardinality::Pairwise, Cardinality::Sequential] {
+                        let normal = expr.normal_with(cardinality);
+                        assert!(
+                            equivalent(&expr, &normal, count),
+                            "{expr:?} encoded {cardinality:?} as {normal:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn registers_preserve_validity() -> Result<(), String> {
+    log_init();
+
+    // The constraint holds just when its encoding holds for every value of
+    // the registers
+    let holds = |expr: &Expr, assignment: &Map<String, bool>| -> Result<bool, String> {
+        let registers = expr
+            .names()
+            .into_iter()
+            .filter(|name| !assignment.contains_key(name))
+            .collect::<Vec<_>>();
+        (0..1u32 << registers.len()).try_fold(true, |holds, row| {
+            let mut assignment = assignment.clone();
+            assignment.extend(
+                registers
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| (name.to_owned(), row & (1 << index) != 0)),
+            );
+            Ok(holds && expr.evaluate(&assignment)?)
+        })
+    };
+
+    for count in 0..=3 {
+        let exprs = atoms(count);
+        for bound in 0..=count + 1 {
+            for constraint in [
+                Expr::at_most(bound, &exprs),
+                Expr::at_least(bound, &exprs),
+                Expr::exactly(bound, &exprs),
+            ] {
+                for expr in [constraint.clone(), Expr::not(constraint.clone())] {
+                    let normal = expr.normal_with(Cardinality::Registers);
+                    for row in 0..1u32 << count {
+                        let assignment = (0..count)
+                            .map(|index| (format!("x{index}"), row & (1 << index) != 0))
+                            .collect::<Map<_, _>>();
+                        assert_eq!(
+                            expr.evaluate(&assignment)?,
+                            holds(&normal, &assignment)?,
+                            "{expr:?} encoded as {normal:?} on {assignment:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn registers_grow_linearly() -> Result<(), String> {
+    log_init();
+
+    // Shared subtrees are walked again each time they occur
+    let size = |count: usize, cardinality: Cardinality| {
+        let mut nodes = 0;
+        Expr::exactly(1, &atoms(count))
+            .normal_with(cardinality)
+            .walk(&mut |_: &Expr| nodes += 1);
+        nodes
+    };
+    for (cardinality, linear) in [
+        (Cardinality::Sequential, false),
+        (Cardinality::Registers, true),
+    ] {
+        let (half, full) = (size(32, cardinality), size(64, cardinality));
+        assert_eq!(
+            full < 3 * half,
+            linear,
+            "{cardinality:?} {half} then {full}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn prove_cardinality() -> Result<(), String> {
+    log_init();
+
+    for cardinality in [
+        Cardinality::Pairwise,
+        Cardinality::Sequential,
+        Cardinality::Registers,
+    ] {
+        for input in [
+            "atmost(1; a, b) | (a & b)",
+            "exactly(1; a, b) > (a | b)",
+            "atleast(1; a, ~a)",
+        ] {
+            let expr = Expr::parse(input)?.normal_with(cardinality);
+            assert!(expr.coalesce().is_some(), "{input} by {cardinality:?}");
+        }
+        // Coalescence is slow to fail over the registers of two operands
+        let input = match cardinality {
+            Cardinality::Registers => "atleast(1; a)",
+            _ => "atmost(1; a, b)",
+        };
+        let expr = Expr::parse(input)?.normal_with(cardinality);
+        assert!(expr.coalesce().is_none(), "{input} by {cardinality:?}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use coalescence::{expression::Expr, parseable::Parseable, Map};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+// Every assignment of signs to the atoms, as in c/1-dnf .. c/4-dnf
+fn minterms(names: &[&str]) -> Result<Expr, String> {
+    Expr::and(
+        &names
+            .iter()
+            .map(|name| {
+                Expr::or(&[
+                    Expr::Atom(name.to_string()),
+                    Expr::NotAtom(name.to_string()),
+                ])
+            })
+            .collect::<Vec<_>>(),
+    )
+    .to_dnf()
+}
+
+fn assignments(expr: &Expr) -> Vec<Map<String, bool>> {
+    let names = expr.names();
+    (0..1 << names.len())
+        .map(|row: u32| {
+            names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.to_owned(), row & (1 << index) != 0))
+                .collect()
+        })
+        .collect()
+}
+
+fn equivalent(left: &Expr, right: &Expr) -> bool {
+    assignments(&Expr::and(&[left.clone(), right.clone()]))
+        .iter()
+        .all(|assignment| left.evaluate(assignment) == right.evaluate(assignment))
+}
+
+fn satisfiable(expr: &Expr) -> bool {
+    assignments(expr)
+        .iter()
+        .any(|assignment| expr.evaluate(assignment) == Ok(true))
+}
+
+#[test]
+fn cnf() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(
+        Expr::parse("a | (b & c)")?.to_cnf()?,
+        Expr::parse("(a | b) & (a | c)")?.normal()
+    );
+    assert_eq!(Expr::parse("a | ~a")?.to_cnf()?, Expr::top());
+
+    for input in ["a > (b > a)", "(a = b) | c", "~((a | b) & (c | d))"] {
+        let expr = Expr::parse(input)?;
+        assert!(equivalent(&expr.to_cnf()?, &expr));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dnf() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(
+        Expr::parse("a & (b | c)")?.to_dnf()?,
+        Expr::parse("(a & b) | (a & c)")?.normal()
+    );
+    assert_eq!(Expr::parse("a & ~a")?.to_dnf()?, Expr::bottom());
+
+    for input in ["a > (b > a)", "(a = b) | c", "~((a | b) & (c | d))"] {
+        let expr = Expr::parse(input)?;
+        assert!(equivalent(&expr.to_dnf()?, &expr));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dnf_minterms() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(
+        minterms(&["a", "b", "c"])?,
+        Expr::parse("(a & b & c) | (a & ~b & c) | (~a & b & c) | (~a & ~b & c) | (a & b & ~c) | (a & ~b & ~c) | (~a & b & ~c) | (~a & ~b & ~c)")?.normal()
+    );
+    for count in 1..=4 {
+        let names = ["a", "b", "c", "d"];
+        match minterms(&names[..count])? {
+            Expr::Or(terms) if count > 1 => assert_eq!(terms.len(), 1 << count),
+            expr => assert_eq!(expr, Expr::parse("a | ~a")?.normal()),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn size_guard() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("(a & b) | (c & d) | (e & f) | (g & h)")?;
+    assert!(expr.to_cnf_within(8).is_err());
+    assert!(expr.to_cnf_within(16).is_ok());
+
+    // Bounded before distributing, though tautologies are dropped after
+    let expr = Expr::parse("(a & ~b) | (b & ~a)")?;
+    assert!(expr.to_cnf_within(3).is_err());
+    assert!(expr.to_cnf_within(4).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn cnf_tseitin() -> Result<(), String> {
+    log_init();
+
+    // Satisfiable iff the original is
+    for input in ["a & ~a", "a | b", "(a > b) & a & ~b", "(a = b) & (b = c)"] {
+        let expr = Expr::parse(input)?;
+        let tseitin = expr.to_cnf_tseitin();
+        assert!(tseitin.names().iter().any(|name| name.starts_with("_t")));
+        assert_eq!(satisfiable(&tseitin), satisfiable(&expr));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    io::{ErrorKind, Write},
+    process::{Command, Stdio},
+};
+
+use pretty_assertions::assert_eq;
+
+fn run(args: &[&str], input: &str) -> Result<(Option<i32>, String), String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_coalescence"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    // The command may fail and exit before reading all of its input
+    match child
+        .stdin
+        .take()
+        .ok_or("No stdin")?
+        .write_all(input.as_bytes())
+    {
+        Err(err) if err.kind() != ErrorKind::BrokenPipe => return Err(err.to_string()),
+        _ => (),
+    }
+    let output = child.wait_with_output().map_err(|err| err.to_string())?;
+    Ok((
+        output.status.code(),
+        String::from_utf8(output.stdout).map_err(|err| err.to_string())?,
+    ))
+}
+
+#[test]
+fn prove_valid() -> Result<(), String> {
+    let (code, stdout) = run(&["prove"], "a > a\n\n(a > a) & (b > b)\n")?;
+
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout.matches("valid: ").count(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn prove_invalid() -> Result<(), String> {
+    let (code, stdout) = run(&["prove"], "a > a\n(a & b) | ~a\n")?;
+
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains("invalid: (a & b) | ~(a)"));
+
+    Ok(())
+}
+
+#[test]
+fn prove_compact() -> Result<(), String> {
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/../c/2-dnf");
+    let (code, stdout) = run(&["prove", "--syntax", "compact", fixture], "")?;
+
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout.matches("valid: ").count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn prove_prolog() -> Result<(), String> {
+    let (code, stdout) = run(
+        &["prove", "--syntax", "prolog"],
+        "a, a -> b --> b\n~(a /\\ b) <--> ~a \\/ ~b\n",
+    )?;
+
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout.matches("valid: ").count(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn prove_chained() -> Result<(), String> {
+    let (code, stdout) = run(&["prove"], "((a > b) & (b > c) & a) > c\n")?;
+
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("valid: "));
+    assert!(!stdout.contains("no proof could be reconstructed"));
+
+    Ok(())
+}
+
+#[test]
+fn prove_stdin_twice() -> Result<(), String> {
+    let (code, stdout) = run(&["prove", "-", "-"], "a > a\n")?;
+
+    assert_eq!(code, Some(2));
+    assert_eq!(stdout, "");
+
+    Ok(())
+}
+
+#[test]
+fn parse_check() -> Result<(), String> {
+    assert_eq!(
+        run(&["parse", "--check"], "a & b\n")?,
+        (Some(0), String::new())
+    );
+
+    assert_eq!(run(&["parse", "--check"], "(a & b\n")?.0, Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn convert() -> Result<(), String> {
+    assert_eq!(
+        run(&["convert", "--to", "json"], "a | ~b\n")?,
+        (
+            Some(0),
+            "{\"Or\":[{\"Not\":{\"Atom\":\"b\"}},{\"Atom\":\"a\"}]}\n".to_string()
+        )
+    );
+
+    assert_eq!(
+        run(
+            &["convert", "--from", "json", "--to", "tex"],
+            "{\"NotAtom\":\"b\"}\n"
+        )?,
+        (Some(0), "\\neg b\n".to_string())
+    );
+
+    // Only syntaxes that can be written are accepted by --to, and only those
+    // that can be read by --from
+    assert_eq!(run(&["convert", "--to", "compact"], "a\n")?.0, Some(2));
+    assert_eq!(run(&["convert", "--to", "prolog"], "a\n")?.0, Some(2));
+    assert_eq!(run(&["convert", "--from", "tex"], "a\n")?.0, Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn repl() -> Result<(), String> {
+    let (code, stdout) = run(
+        &["repl"],
+        "a > a\n(a & b) | ~a\naxioms(a > b)\na > b\noutput(tex)\n1\nquit\n",
+    )?;
+
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("Succeeded in proving a > a"));
+    assert!(stdout.contains("Failed to prove (a & b) | ~(a)"));
+    assert!(stdout.contains("Succeeded in proving a > b using [A1]"));
+    assert!(stdout.contains("tex:1 =\n\\begin{prooftree}"));
+
+    Ok(())
+}
+
+#[test]
+fn repl_arguments() -> Result<(), String> {
+    let (code, stdout) = run(
+        &["repl"],
+        "log( no )\nlog\nthreshold( 3 )\nthreshold\noutput( tex )\noutput\nquit\n",
+    )?;
+
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("log(no)"));
+    assert!(stdout.contains("threshold(3)"));
+    assert!(stdout.contains("output(tex)"));
+    assert!(!stdout.contains("error: "), "{stdout}");
+    // Rather than logging to a file named no
+    assert!(!std::path::Path::new("no").exists());
+
+    Ok(())
+}
@@ -9,6 +9,30 @@ fn log_init() {
     let _ = env_logger::builder().is_test(true).try_init();
 }
 
+// Every assignment of signs to the atoms, as in c/1-dnf .. c/4-dnf
+const SECOND_AXIOM: &str = "(a & b) | (~a & b) | (a & ~b) | (~a & ~b)";
+const THIRD_AXIOM: &str = "(a & b & c) | (a & ~b & c) | (~a & b & c) | (~a & ~b & c) | (a & b & ~c) | (a & ~b & ~c) | (~a & b & ~c) | (~a & ~b & ~c)";
+const FOURTH_AXIOM: &str = "(a & b & c & d) | (a & ~b & c & d) | (~a & b & c & d) | (~a & ~b & c & d) | (a & b & ~c & d) | (a & ~b & ~c & d) | (~a & b & ~c & d) | (~a & ~b & ~c & d) | (a & b & c & ~d) | (a & ~b & c & ~d) | (~a & b & c & ~d) | (~a & ~b & c & ~d) | (a & b & ~c & ~d) | (a & ~b & ~c & ~d) | (~a & b & ~c & ~d) | (~a & ~b & ~c & ~d)";
+
+#[test]
+fn axiom_fixtures() -> Result<(), String> {
+    log_init();
+
+    for (names, fixture) in [
+        (&["a", "b"][..], SECOND_AXIOM),
+        (&["a", "b", "c"], THIRD_AXIOM),
+        (&["a", "b", "c", "d"], FOURTH_AXIOM),
+    ] {
+        let source = names
+            .iter()
+            .map(|name| format!("({name} | ~{name})"))
+            .collect::<Vec<_>>()
+            .join(" & ");
+        assert_eq!(Expr::parse(&source)?.to_dnf()?, Expr::parse(fixture)?.normal());
+    }
+    Ok(())
+}
+
 #[test]
 fn coalesce_axiom() -> Result<(), String> {
     log_init();
@@ -40,7 +64,7 @@ fn coalesce_two_axioms() -> Result<(), String> {
 fn coalesce_second_axiom() -> Result<(), String> {
     log_init();
 
-    let expr = Expr::parse("(a & b) | (~a & b) | (a & ~b) | (~a & ~b)")?.normal();
+    let expr = Expr::parse(SECOND_AXIOM)?.normal();
     let _ = expr.coalesce().ok_or("Not coalesceable")?;
     Ok(())
 }
@@ -61,7 +85,7 @@ fn coalesce_second_axiom_invalid() -> Result<(), String> {
 fn coalesce_third_axiom() -> Result<(), String> {
     log_init();
 
-    let expr = Expr::parse("(a & b & c) | (a & ~b & c) | (~a & b & c) | (~a & ~b & c) | (a & b & ~c) | (a & ~b & ~c) | (~a & b & ~c) | (~a & ~b & ~c)")?.normal();
+    let expr = Expr::parse(THIRD_AXIOM)?.normal();
     let _ = expr.coalesce().ok_or("Not coalesceable")?;
     Ok(())
 }
@@ -71,7 +95,42 @@ fn coalesce_third_axiom() -> Result<(), String> {
 pub fn coalesce_fourth_axiom() -> Result<(), String> {
     log_init();
 
-    let expr = Expr::parse("(a & b & c & d) | (a & ~b & c & d) | (~a & b & c & d) | (~a & ~b & c & d) | (a & b & ~c & d) | (a & ~b & ~c & d) | (~a & b & ~c & d) | (~a & ~b & ~c & d) | (a & b & c & ~d) | (a & ~b & c & ~d) | (~a & b & c & ~d) | (~a & ~b & c & ~d) | (a & b & ~c & ~d) | (a & ~b & ~c & ~d) | (~a & b & ~c & ~d) | (~a & ~b & ~c & ~d)")?.normal();
+    let expr = Expr::parse(FOURTH_AXIOM)?.normal();
     let _ = expr.coalesce().ok_or("Not coalesceable")?;
     Ok(())
 }
+
+#[test]
+fn coalesce_exhausted_projections() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("((c > a) & c) = a")?.normal();
+    let _ = expr
+        .coalesce()
+        .ok_or("Not coalesceable")
+        .expect_err("False statement coalesceable");
+
+    // Without a bound on dimension, fails once projecting adds no new tokens,
+    // rather than projecting and firing on none forever
+    let expr = Expr::parse("a & (b | ~b)")?.normal();
+    let _ = expr
+        .coalesce_within(usize::MAX)
+        .ok_or("Not coalesceable")
+        .expect_err("False statement coalesceable");
+    Ok(())
+}
+
+#[test]
+fn coalesce_within_dim_bound() -> Result<(), String> {
+    log_init();
+
+    // Axioms are spawned without projecting, whatever the bound
+    let expr = Expr::parse("a > a")?.normal();
+    let _ = expr.coalesce_within(0).ok_or("Not coalesceable")?;
+
+    let expr = Expr::parse("(a & b) | (a & ~b) | (~a & b) | (~a & ~b)")?.normal();
+    assert!(expr.coalesce_within(0).is_none());
+    assert!(expr.coalesce_within(1).is_none());
+    let _ = expr.coalesce_within(2).ok_or("Not coalesceable")?;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use coalescence::{
+    dimacs::{parse_dimacs, Encoding},
+    expression::Expr,
+    parseable::Parseable,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+#[test]
+fn parse_instance() -> Result<(), String> {
+    log_init();
+
+    let input = "\
+c A simple instance
+c 3 variables
+p cnf 3 2
+1 -3 0
+2 3
+-1 0
+";
+    assert_eq!(
+        parse_dimacs(input)?,
+        Expr::parse("(1 | ~3) & (2 | 3 | ~1)")?.normal()
+    );
+
+    assert_eq!(
+        parse_dimacs("c var 1 a b\np cnf 1 1\n-1 0\n")?,
+        Expr::NotAtom("a b".to_string())
+    );
+
+    assert!(parse_dimacs("p cnf 1 1\n2 0\n").is_err());
+    assert!(parse_dimacs("p cnf 2 2\n1 2 0\n").is_err());
+    assert!(parse_dimacs("1 2 0\n").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn write_instance() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("a & (b | ~c)")?;
+    assert_eq!(
+        expr.to_dimacs(Encoding::Distribute)?,
+        "\
+c var 1 a
+c var 2 b
+c var 3 c
+p cnf 3 2
+2 -3 0
+1 0
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn round_trip() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("(a = b) | ~(c & d)")?;
+    assert_eq!(
+        parse_dimacs(&expr.to_dimacs(Encoding::Distribute)?)?,
+        expr.to_cnf()?
+    );
+    assert_eq!(
+        parse_dimacs(&expr.to_dimacs(Encoding::Tseitin)?)?,
+        expr.to_cnf_tseitin()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use coalescence::{entailment::entails, expression::Expr, parseable::Parseable};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+fn parse_all(inputs: &[&str]) -> Result<Vec<Expr>, String> {
+    inputs.iter().map(|input| Expr::parse(input)).collect()
+}
+
+#[test]
+fn entails_modus_ponens() -> Result<(), String> {
+    log_init();
+
+    let premises = parse_all(&["a > b", "a"])?;
+    let entailment = entails(&premises, &Expr::parse("b")?).ok_or("Not entailed")?;
+
+    assert_eq!(entailment.core, vec![0, 1]);
+    assert_eq!(entailment.proof.sequent.antecedent, premises);
+
+    Ok(())
+}
+
+#[test]
+fn entails_not() -> Result<(), String> {
+    log_init();
+
+    let premises = parse_all(&["a > b", "b"])?;
+
+    assert_eq!(entails(&premises, &Expr::parse("a")?), None);
+
+    Ok(())
+}
+
+#[test]
+fn entails_core() -> Result<(), String> {
+    log_init();
+
+    let premises = parse_all(&["c", "a > b", "d | e", "a", "b > c"])?;
+    let entailment = entails(&premises, &Expr::parse("c")?).ok_or("Not entailed")?;
+
+    assert_eq!(entailment.core, vec![0]);
+
+    let premises = parse_all(&["d", "a > b", "d | e", "a", "b > c"])?;
+    let entailment = entails(&premises, &Expr::parse("c")?).ok_or("Not entailed")?;
+
+    assert_eq!(entailment.core, vec![1, 3, 4]);
+
+    Ok(())
+}
+
+#[test]
+fn entails_tautology() -> Result<(), String> {
+    log_init();
+
+    let premises = parse_all(&["a", "b"])?;
+    let entailment = entails(&premises, &Expr::parse("c | ~c")?).ok_or("Not entailed")?;
+
+    assert_eq!(entailment.core, Vec::<usize>::new());
+
+    Ok(())
+}
+
+#[test]
+fn entails_chain() -> Result<(), String> {
+    log_init();
+
+    let premises = parse_all(&["b > c", "e", "a", "a > b"])?;
+    let entailment = entails(&premises, &Expr::parse("c")?).ok_or("Not entailed")?;
+
+    assert_eq!(entailment.core, vec![0, 2, 3]);
+    assert_eq!(
+        entailment.proof.sequent.antecedent,
+        parse_all(&["b > c", "a", "a > b"])?
+    );
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use coalescence::{expression::*, parseable::Parseable, Set};
+use coalescence::{coalesceable::Coalesceable, expression::*, parseable::Parseable, Map, Set};
 
 use pretty_assertions::assert_eq;
 
@@ -146,3 +146,218 @@ fn lineage() -> Result<(), String> {
 
     Ok(())
 }
+
+#[test]
+fn digest() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(Expr::parse("a")?.digest(), 0x15e21d2d38e16316);
+
+    assert_eq!(
+        Expr::parse("a | (b & c)")?.normal().digest(),
+        Expr::parse("(c & b) | a")?.normal().digest()
+    );
+
+    assert_ne!(Expr::parse("a")?.digest(), Expr::parse("~a")?.digest());
+    assert_ne!(
+        Expr::parse("a & b")?.digest(),
+        Expr::parse("a | b")?.digest()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn evaluate() -> Result<(), String> {
+    log_init();
+
+    let assignment = Map::from([("a".to_string(), true), ("b".to_string(), false)]);
+
+    assert_eq!(Expr::parse("a > b")?.evaluate(&assignment), Ok(false));
+    assert_eq!(Expr::parse("~a | ~b")?.evaluate(&assignment), Ok(true));
+    assert!(Expr::parse("a & c")?.evaluate(&assignment).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn equivalent() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(
+        Expr::parse("~(a & b)")?.equivalent(&Expr::parse("~a | ~b")?),
+        Ok(())
+    );
+    assert_eq!(
+        Expr::parse("a > b")?.equivalent(&Expr::parse("~b > ~a")?),
+        Ok(())
+    );
+
+    let lhs = Expr::parse("a > b")?;
+    let rhs = Expr::parse("b > a")?;
+    let assignment = lhs.equivalent(&rhs).unwrap_err().ok_or("No assignment")?;
+    assert_ne!(lhs.evaluate(&assignment), rhs.evaluate(&assignment));
+
+    // More names than the truth table could be searched over
+    let names = (0..40)
+        .map(|index| Expr::Atom(format!("a{index}")))
+        .collect::<Vec<_>>();
+    let lhs = Expr::or(&names);
+    let rhs = Expr::not(Expr::and(
+        &names.iter().cloned().map(Expr::not).collect::<Vec<_>>(),
+    ));
+    assert_eq!(lhs.equivalent(&rhs), Ok(()));
+
+    Ok(())
+}
+
+#[test]
+fn simplify() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(Expr::parse("a & ~a")?.simplify(), Expr::bottom());
+    assert_eq!(Expr::parse("a | ~a")?.simplify(), Expr::top());
+    assert_eq!(Expr::parse("a | (a & b)")?.simplify(), Expr::parse("a")?);
+    assert_eq!(Expr::parse("a & (a | b)")?.simplify(), Expr::parse("a")?);
+    assert_eq!(
+        Expr::parse("a & (~a | b)")?.simplify(),
+        Expr::parse("a & b")?
+    );
+    assert_eq!(
+        Expr::parse("(a | b) & (a | b | c) & d")?.simplify(),
+        Expr::parse("(a | b) & d")?
+    );
+    assert_eq!(Expr::parse("c & (a | ~a)")?.simplify(), Expr::parse("c")?);
+    assert_eq!(Expr::parse("c | (a & ~a)")?.simplify(), Expr::parse("c")?);
+
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct Formula(Expr);
+
+impl quickcheck::Arbitrary for Formula {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        fn expr(g: &mut quickcheck::Gen, depth: usize) -> Expr {
+            let name = g.choose(&["a", "b", "c", "d"]).unwrap().to_string();
+            let choice = match depth {
+                0 => u8::arbitrary(g) % 4,
+                _ => u8::arbitrary(g) % 7,
+            };
+            let exprs = |g: &mut quickcheck::Gen| {
+                (0..1 + usize::arbitrary(g) % 3)
+                    .map(|_| expr(g, depth - 1))
+                    .collect::<Vec<_>>()
+            };
+            match choice {
+                0 => Expr::Atom(name),
+                1 => Expr::NotAtom(name),
+                2 => Expr::top(),
+                3 => Expr::bottom(),
+                4 => Expr::and(&exprs(g)),
+                5 => Expr::or(&exprs(g)),
+                _ => Expr::not(expr(g, depth - 1)),
+            }
+        }
+        Formula(expr(g, 4))
+    }
+}
+
+fn assignments(names: &Set<String>) -> Vec<Map<String, bool>> {
+    (0..1 << names.len())
+        .map(|row: u32| {
+            names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.to_owned(), row & (1 << index) != 0))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn simplify_preserves_equivalence() {
+    log_init();
+
+    fn property(formula: Formula) -> bool {
+        let Formula(expr) = formula;
+        let simple = expr.simplify();
+        assignments(&expr.names())
+            .iter()
+            .all(|assignment| expr.evaluate(assignment) == simple.evaluate(assignment))
+            && simple.simplify() == simple
+    }
+    quickcheck::quickcheck(property as fn(Formula) -> bool);
+}
+
+#[test]
+fn substitute() -> Result<(), String> {
+    log_init();
+
+    let replacements = Map::from([
+        ("a".to_string(), Expr::parse("x & y")?),
+        ("b".to_string(), Expr::parse("a")?),
+    ]);
+    assert_eq!(
+        Expr::parse("a | b | c")?.substitute(&replacements),
+        Expr::parse("(x & y) | a | c")?
+    );
+    assert_eq!(
+        Expr::NotAtom("a".to_string()).substitute(&replacements),
+        Expr::parse("~x | ~y")?.normal()
+    );
+    assert_eq!(
+        Expr::parse("~a > atmost(1; a, b)")?.substitute(&replacements),
+        Expr::parse("~(x & y) > atmost(1; x & y, a)")?
+    );
+
+    assert_eq!(
+        Expr::parse("a & ~b")?.rename_atoms(&Map::from([
+            ("a".to_string(), "b".to_string()),
+            ("b".to_string(), "a".to_string())
+        ])),
+        Expr::parse("b & ~a")?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn substitute_preserves_validity() -> Result<(), String> {
+    log_init();
+
+    let guards = Map::from([
+        ("a".to_string(), Expr::parse("enabled & (mode = fast)")?),
+        ("b".to_string(), Expr::parse("~enabled | ready")?),
+    ]);
+    for schema in ["a | ~a", "(a > b) | (b > a)", "(a & b) > a", "~(a & ~a)"] {
+        let schema = Expr::parse(schema)?;
+        assert!(schema.normal().coalesce().is_some());
+        let instance = schema.substitute(&guards);
+        assert!(instance.normal().coalesce().is_some(), "{instance:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn substitute_evaluates_replacements() {
+    log_init();
+
+    // Evaluating a substitution is evaluating the schema with each atom valued
+    // as its replacement, so in particular valid schemas stay valid
+    fn property(schema: Formula, a: Formula, b: Formula) -> bool {
+        let (Formula(schema), Formula(a), Formula(b)) = (schema, a, b);
+        let replacements = Map::from([("a".to_string(), a), ("b".to_string(), b)]);
+        let instance = schema.substitute(&replacements);
+        let names = ["a", "b", "c", "d"].map(str::to_string).into();
+        assignments(&names).iter().all(|assignment| {
+            let mut valued = assignment.clone();
+            for (name, replacement) in &replacements {
+                valued.insert(name.to_owned(), replacement.evaluate(assignment).unwrap());
+            }
+            instance.evaluate(assignment) == schema.evaluate(&valued)
+        })
+    }
+    quickcheck::quickcheck(property as fn(Formula, Formula, Formula) -> bool);
+}
@@ -0,0 +1,95 @@
+use coalescence::{
+    expression::Expr,
+    lemma::LemmaStore,
+    parseable::Parseable,
+    proof::{Proof, Rule},
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+fn rules(proof: &Proof) -> Vec<&Rule> {
+    [&proof.rule]
+        .into_iter()
+        .chain(proof.premises.iter().flat_map(rules))
+        .collect()
+}
+
+#[test]
+fn prove_lemma() -> Result<(), String> {
+    log_init();
+
+    let mut store = LemmaStore::new();
+    let lemma = Expr::parse("a > (b > a)")?;
+    let proof = store.prove(&lemma).ok_or("Not proven")?;
+
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.get(&lemma), Some(&proof));
+    assert_eq!(store.prove(&lemma), Some(proof));
+
+    assert_eq!(store.prove(&Expr::parse("a")?), None);
+    assert_eq!(store.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn cite_lemma() -> Result<(), String> {
+    log_init();
+
+    let mut store = LemmaStore::new();
+    let lemma = Expr::parse("a > (b > a)")?.normal();
+    store.prove(&lemma).ok_or("Not proven")?;
+
+    let goal = Expr::parse("(a > (b > a)) & (c | ~c)")?;
+    let proof = store.prove(&goal).ok_or("Not proven")?;
+
+    assert!(rules(&proof).contains(&&Rule::Lemma(lemma)));
+    assert_eq!(store.len(), 2);
+
+    let expanded = store.expand(&proof);
+    assert!(!rules(&expanded)
+        .iter()
+        .any(|rule| matches!(rule, Rule::Lemma(_))));
+    assert_eq!(expanded.token, proof.token);
+
+    Ok(())
+}
+
+#[test]
+fn cite_lemma_instance() -> Result<(), String> {
+    log_init();
+
+    let mut store = LemmaStore::new();
+    store
+        .prove(&Expr::parse("a > (b > a)")?)
+        .ok_or("Not proven")?;
+
+    let instance = Expr::parse("c > (d > c)")?.normal();
+    // A failed proof leaves the store as it was
+    assert_eq!(store.prove(&Expr::parse("(c > (d > c)) & e")?), None);
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.get(&instance), None);
+
+    let goal = Expr::parse("(c > (d > c)) & (e | ~e)")?;
+    let proof = store.prove(&goal).ok_or("Not proven")?;
+
+    assert!(rules(&proof).contains(&&Rule::Lemma(instance.clone())));
+    assert_eq!(
+        store.get(&instance).map(|proof| &proof.token),
+        Some(&[instance].into())
+    );
+
+    // Atoms are renamed one to one, never merged
+    let proof = store
+        .prove(&Expr::parse("(c > (c > c)) & (e | ~e)")?)
+        .ok_or("Not proven")?;
+    assert!(!rules(&proof)
+        .iter()
+        .any(|rule| matches!(rule, Rule::Lemma(_))));
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use coalescence::{expression::Expr, parseable::*};
+use std::{fs, path::Path};
+
+use coalescence::{expression::Expr, parseable::*, Map};
 
 use pretty_assertions::assert_eq;
 
@@ -16,6 +18,9 @@ fn parse_atom() -> Result<(), String> {
 
     assert_eq!(Expr::parse("~a")?, Expr::not(Expr::Atom("a".to_string())));
 
+    // Reserved for fresh atoms
+    assert!(Expr::parse("_r0_1_1 | a").is_err());
+
     Ok(())
 }
 
@@ -42,25 +47,175 @@ fn parse_syntactic_sugar() -> Result<(), String> {
 
     assert_eq!(
         Expr::parse("a > b")?,
-        Expr::or(&[
-            Expr::not(Expr::Atom("a".to_string())),
-            Expr::Atom("b".to_string())
-        ])
+        Expr::implies(Expr::Atom("a".to_string()), Expr::Atom("b".to_string()))
     );
 
     assert_eq!(
         Expr::parse("a = b")?,
+        Expr::iff(Expr::Atom("a".to_string()), Expr::Atom("b".to_string()))
+    );
+
+    // Right associative, as are & and |
+    assert_eq!(
+        Expr::parse("a > b > c")?,
+        Expr::implies(
+            Expr::Atom("a".to_string()),
+            Expr::implies(Expr::Atom("b".to_string()), Expr::Atom("c".to_string()))
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn print_syntactic_sugar() -> Result<(), String> {
+    log_init();
+
+    for input in [
+        "a > b",
+        "a = b",
+        "(a > b) = (~(b) > ~(a))",
+        "(a & b) > (a | c)",
+    ] {
+        let expr = Expr::parse(input)?;
+        assert_eq!(format!("{expr:?}"), input);
+        assert_eq!(Expr::parse(&format!("{expr:?}"))?, expr);
+    }
+    assert_eq!(format!("{:?}", Expr::parse("a & (b > c)")?), "(b > c) & a");
+
+    assert_eq!(Expr::parse("T")?, Expr::top());
+    assert_eq!(Expr::parse("F")?, Expr::bottom());
+    for expr in [
+        Expr::and(&[Expr::parse("a")?, Expr::top()]),
+        Expr::or(&[Expr::parse("a")?, Expr::bottom()]),
+        Expr::implies(Expr::not(Expr::top()), Expr::bottom()),
+        parse_compact("(av(T^~F))")?,
+    ] {
+        assert_eq!(Expr::parse(&format!("{expr:?}"))?, expr);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parse_extra_connectives() -> Result<(), String> {
+    log_init();
+
+    let a = || Expr::Atom("a".to_string());
+    let b = || Expr::Atom("b".to_string());
+    assert_eq!(Expr::parse("a < b")?, Expr::implies(b(), a()));
+    assert_eq!(Expr::parse("a # b")?, Expr::xor(a(), b()));
+    assert_eq!(Expr::parse("a ^ b")?, Expr::xor(a(), b()));
+    assert_eq!(Expr::parse("a ~& b")?, Expr::not(Expr::and(&[a(), b()])));
+    assert_eq!(Expr::parse("a ~| b")?, Expr::not(Expr::or(&[a(), b()])));
+    assert_eq!(format!("{:?}", Expr::parse("(a ^ b) ^ c")?), "(a ^ b) ^ c");
+
+    Ok(())
+}
+
+#[test]
+fn normal_extra_connectives() -> Result<(), String> {
+    log_init();
+
+    for (input, expected) in [
+        ("a < b", "a | ~b"),
+        ("a ^ b", "(a | b) & (~a | ~b)"),
+        ("~(a ^ b)", "(~a | b) & (a | ~b)"),
+        ("a ~& b", "~a | ~b"),
+        ("a ~| b", "~a & ~b"),
+    ] {
+        assert_eq!(
+            Expr::parse(input)?.normal(),
+            Expr::parse(expected)?.normal()
+        );
+    }
+
+    // Parity is true exactly when an odd number of its operands are
+    let parity = Expr::parse("a ^ (b ^ c)")?;
+    for row in 0..8u32 {
+        let assignment = ["a", "b", "c"]
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.to_string(), row & (1 << index) != 0))
+            .collect::<Map<_, _>>();
+        assert_eq!(parity.evaluate(&assignment)?, row.count_ones() % 2 == 1);
+    }
+    assert_eq!(
+        Expr::parse("a ^ b")?.equivalent(&Expr::parse("~(a = b)")?),
+        Ok(())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parse_indexed_atoms() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(Expr::parse("p[1]")?, Expr::Atom("p[1]".to_string()));
+    assert_eq!(
+        Expr::parse("p[1, 2] & ~q[3]")?,
         Expr::and(&[
-            Expr::or(&[
-                Expr::not(Expr::Atom("a".to_string())),
-                Expr::Atom("b".to_string())
-            ]),
-            Expr::or(&[
-                Expr::not(Expr::Atom("b".to_string())),
-                Expr::Atom("a".to_string())
-            ])
+            Expr::Atom("p[1,2]".to_string()),
+            Expr::not(Expr::Atom("q[3]".to_string()))
         ])
     );
+    assert_eq!(
+        Expr::parse("AND i in 1..2: p[i, i + 1] | p[i - 1,2-i]")?,
+        Expr::parse("(p[1,2] | p[0,1]) & (p[2,3] | p[1,0])")?
+    );
+
+    assert!(Expr::parse("p[i]").is_err());
+    assert!(Expr::parse("p[1").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn parse_big_operators() -> Result<(), String> {
+    log_init();
+
+    // Expanded flat, where the binary connectives nest
+    assert_eq!(
+        Expr::parse("AND i in 1..4: (p[i] | ~p[i])")?.normal(),
+        Expr::parse("(p[1] | ~p[1]) & (p[2] | ~p[2]) & (p[3] | ~p[3]) & (p[4] | ~p[4])")?.normal()
+    );
+    assert_eq!(
+        Expr::parse("a & (OR i in 1..3: p[i])")?,
+        Expr::and(&[
+            Expr::parse("a")?,
+            Expr::parse("p[1] | p[2] | p[3]")?.normal()
+        ])
+    );
+    // Bounds may depend on enclosing indexes, and empty ranges are constants
+    assert_eq!(
+        Expr::parse("OR i in 1..3: AND j in i + 1..3: q[i,j]")?,
+        Expr::or(&[
+            Expr::parse("q[1,2] & q[1,3]")?,
+            Expr::parse("q[2,3]")?,
+            Expr::top()
+        ])
+    );
+    assert_eq!(Expr::parse("OR i in 1..0: p[i]")?, Expr::bottom());
+
+    // Three pigeons do not fit in two holes
+    let pigeonhole = Expr::parse(
+        "(AND i in 1..3: OR j in 1..2: p[i,j]) & \
+         (AND j in 1..2: AND i in 1..3: AND k in i + 1..3: ~p[i,j] | ~p[k,j])",
+    )?;
+    assert_eq!(pigeonhole.names().len(), 6);
+    assert!((0..1u32 << 6).all(|row| {
+        let assignment = pigeonhole
+            .names()
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| (name, row & (1 << index) != 0))
+            .collect::<Map<_, _>>();
+        pigeonhole.evaluate(&assignment) == Ok(false)
+    }));
+
+    assert!(Expr::parse("AND 1 in 1..2: p").is_err());
+    assert!(Expr::parse("AND i in 1..n: p[i]").is_err());
 
     Ok(())
 }
@@ -78,18 +233,69 @@ fn parse_braces() -> Result<(), String> {
     );
 
     assert_eq!(
-        Expr::parse("a = b")?,
+        Expr::parse("(a = b) > c")?,
+        Expr::implies(
+            Expr::iff(Expr::Atom("a".to_string()), Expr::Atom("b".to_string())),
+            Expr::Atom("c".to_string())
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parse_compact_syntax() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(
+        parse_compact("(av~a)")?,
+        Expr::or(&[Expr::Atom("a".to_string()), Expr::NotAtom("a".to_string())])
+    );
+    assert_eq!(
+        parse_compact("(a^(bvT))")?,
         Expr::and(&[
-            Expr::or(&[
-                Expr::not(Expr::Atom("a".to_string())),
-                Expr::Atom("b".to_string())
-            ]),
-            Expr::or(&[
-                Expr::not(Expr::Atom("b".to_string())),
-                Expr::Atom("a".to_string())
-            ])
+            Expr::parse("a")?,
+            Expr::or(&[Expr::parse("b")?, Expr::top()])
         ])
     );
+    assert_eq!(parse_compact("(a>b)")?, Expr::parse("a > b")?);
+    assert_eq!(parse_compact("(a<b)")?, Expr::parse("b > a")?);
+    assert_eq!(parse_compact("~(a=b)")?, Expr::not(Expr::parse("a = b")?));
+    assert_eq!(parse_compact("(a#b)")?, Expr::parse("a ^ b")?);
+
+    assert!(parse_compact("(a^b").is_err());
+    assert!(parse_compact("(a*b)").is_err());
+    assert!(parse_compact("(a^b)c").is_err());
+    assert!(parse_compact("(^b)").is_err());
+
+    Ok(())
+}
+
+// The c/1-dnf .. c/4-dnf fixtures are every assignment of signs to their atoms
+#[test]
+fn parse_compact_fixtures() -> Result<(), String> {
+    log_init();
+
+    let names = ["a", "b", "c", "d"];
+    for count in 1..=4 {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("../c/{count}-dnf"));
+        let input =
+            fs::read_to_string(&path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let minterms = Expr::and(
+            &names[..count]
+                .iter()
+                .map(|name| {
+                    Expr::or(&[
+                        Expr::Atom(name.to_string()),
+                        Expr::NotAtom(name.to_string()),
+                    ])
+                })
+                .collect::<Vec<_>>(),
+        )
+        .to_dnf()?;
+
+        assert_eq!(parse_compact(&input)?.normal(), minterms);
+    }
 
     Ok(())
 }
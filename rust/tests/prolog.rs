@@ -0,0 +1,68 @@
+use coalescence::{
+    expression::Expr,
+    parseable::Parseable,
+    prolog::{parse_prolog, parse_prolog_formula},
+    sequent::Sequent,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+#[test]
+fn parse_formula() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(parse_prolog_formula("a /\\ ~b")?, Expr::parse("a & ~b")?);
+    assert_eq!(
+        parse_prolog_formula("a -> b -> c")?,
+        Expr::parse("a > (b > c)")?
+    );
+    assert_eq!(
+        parse_prolog_formula("a /\\ b \\/ c")?,
+        Expr::parse("a & (b | c)")?
+    );
+    assert_eq!(parse_prolog_formula("~a \\/ b")?, Expr::parse("~a | b")?);
+    assert_eq!(parse_prolog_formula("(a <-> b).")?, Expr::parse("a = b")?);
+    assert_eq!(
+        parse_prolog_formula("top /\\ 'an atom'")?,
+        Expr::and(&[Expr::top(), Expr::Atom("an atom".to_string())])
+    );
+
+    assert!(parse_prolog_formula("p(a)").is_err());
+    assert!(parse_prolog_formula("X /\\ a").is_err());
+    assert!(parse_prolog_formula("a /\\").is_err());
+    assert!(parse_prolog_formula("a b").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn parse_sequent() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(
+        parse_prolog("a, a -> b --> b")?,
+        vec![Sequent::new(
+            &[Expr::parse("a")?, Expr::parse("a > b")?],
+            &[Expr::parse("b")?]
+        )]
+    );
+    assert_eq!(
+        parse_prolog("[] --> [a \\/ ~a]")?,
+        vec![Sequent::new(&[], &[Expr::parse("a | ~a")?])]
+    );
+    assert_eq!(parse_prolog("--> a")?, parse_prolog("[] --> [a]")?);
+
+    let sequents = parse_prolog("~(a /\\ b) <--> ~a \\/ ~b.")?;
+    assert_eq!(sequents.len(), 2);
+    assert_eq!(sequents[1], sequents[0].converse());
+    assert!(sequents.iter().all(|sequent| sequent.prove().is_some()));
+
+    assert!(parse_prolog("a /\\ b").is_err());
+    assert!(parse_prolog("[a --> b").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use coalescence::{
+    coalesceable::Coalesceable,
+    expression::Expr,
+    parseable::Parseable,
+    proof::{Proof, Rule},
+    Set,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+// Every leaf is an axiom, and every step concludes its own token
+fn leaves_are_axioms(proof: &Proof) -> bool {
+    match proof.rule {
+        Rule::Axiom => proof.premises.is_empty() && proof.token.len() == 2,
+        _ => !proof.premises.is_empty() && proof.premises.iter().all(leaves_are_axioms),
+    }
+}
+
+#[test]
+fn reconstruct_modus_ponens() -> Result<(), String> {
+    log_init();
+
+    for input in [
+        "((a > b) & a) > b",
+        "((a > b) & (b > c) & a) > c",
+        "((a > b) & (b > c) & (c > d) & a) > d",
+    ] {
+        let expr = Expr::parse(input)?.normal();
+        let (_, dag) = expr.coalesce().ok_or(format!("{input} not coalesceable"))?;
+        let proof =
+            Proof::reconstruct(&expr, &dag).ok_or(format!("{input} not reconstructable"))?;
+        assert_eq!(proof.token, Set::from([expr]));
+        assert!(leaves_are_axioms(&proof), "{input} by {proof:?}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use coalescence::{
+    expression::Expr,
+    parseable::Parseable,
+    rewrite::{Order, Pattern, Rewrite, Rewriter, Visitor},
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+fn double_negation(expr: &Expr) -> Option<Expr> {
+    match expr {
+        Expr::Not(expr) => match &**expr {
+            Expr::Not(expr) => Some(*expr.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// a & (a | b) to a
+fn absorption(expr: &Expr) -> Option<Expr> {
+    match expr {
+        Expr::And(exprs) => exprs.iter().find_map(|expr| match &**expr {
+            Expr::Or(disjuncts) if disjuncts.iter().any(|disjunct| exprs.contains(disjunct)) => {
+                Some(Expr::And(
+                    exprs
+                        .iter()
+                        .filter(|conjunct| *conjunct != expr)
+                        .cloned()
+                        .collect(),
+                ))
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+fn singleton(expr: &Expr) -> Option<Expr> {
+    match expr {
+        Expr::And(exprs) | Expr::Or(exprs) if exprs.len() == 1 => {
+            exprs.first().map(|expr| *expr.clone())
+        }
+        _ => None,
+    }
+}
+
+fn nested(depth: usize) -> Expr {
+    (0..depth).fold(Expr::Atom("a".to_string()), |expr, _| Expr::not(expr))
+}
+
+#[derive(Default)]
+struct Depth {
+    current: usize,
+    max: usize,
+    leaves: Vec<String>,
+}
+
+impl Visitor for Depth {
+    fn enter(&mut self, expr: &Expr) -> bool {
+        self.current += 1;
+        self.max = self.max.max(self.current);
+        if let Expr::Atom(name) = expr {
+            self.leaves.push(name.to_string());
+        }
+        true
+    }
+
+    fn leave(&mut self, _expr: &Expr) {
+        self.current -= 1;
+    }
+}
+
+#[test]
+fn walk() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("(a > ~(b & c)) & d")?;
+    let mut depth = Depth::default();
+    expr.walk(&mut depth);
+    assert_eq!(depth.current, 0);
+    assert_eq!(depth.max, 5);
+    assert_eq!(depth.leaves, vec!["a", "b", "c", "d"]);
+
+    let mut count = 0;
+    expr.walk(&mut |_: &Expr| count += 1);
+    assert_eq!(count, 8);
+
+    Ok(())
+}
+
+#[test]
+fn fold() -> Result<(), String> {
+    log_init();
+
+    // Dualise, swapping conjunction and disjunction
+    let dual = Expr::parse("(a & ~b) | (c > (d & e))")?.fold(&mut |expr: Expr| match expr {
+        Expr::And(exprs) => Expr::Or(exprs),
+        Expr::Or(exprs) => Expr::And(exprs),
+        expr => expr,
+    });
+    assert_eq!(dual, Expr::parse("(a | ~b) & (c > (d | e))")?);
+
+    let expr = Expr::parse("atmost(1; a, b ^ c, ~d)")?;
+    assert_eq!(expr.fold(&mut |expr| expr), expr);
+
+    Ok(())
+}
+
+#[test]
+fn rewrite_to_fixpoint() -> Result<(), String> {
+    log_init();
+
+    for order in [Order::BottomUp, Order::TopDown] {
+        let rewriter = Rewriter::new(
+            vec![
+                Box::new(double_negation),
+                Box::new(absorption),
+                Box::new(singleton),
+            ],
+            order,
+        );
+        assert_eq!(
+            rewriter.rewrite(&Expr::parse("~~(~~a & (b | ~~~~a)) > ~~~c")?)?,
+            Expr::parse("a > ~c")?,
+            "{order:?}"
+        );
+        assert_eq!(
+            rewriter.rewrite(&Expr::parse("a | b")?)?,
+            Expr::parse("a | b")?
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rewrite_order() -> Result<(), String> {
+    log_init();
+
+    // Top down sees a & b before its operands are rewritten
+    let rules = || -> Vec<Box<dyn Rewrite>> {
+        vec![
+            Box::new(|expr: &Expr| match expr {
+                Expr::And(_) if *expr == Expr::parse("a & b").unwrap() => {
+                    Some(Expr::Atom("c".to_string()))
+                }
+                _ => None,
+            }),
+            Box::new(|expr: &Expr| match expr {
+                Expr::Atom(name) if name == "a" => Some(Expr::Atom("d".to_string())),
+                _ => None,
+            }),
+        ]
+    };
+    let expr = Expr::parse("(a & b) | a")?;
+    assert_eq!(
+        Rewriter::new(rules(), Order::BottomUp).rewrite(&expr)?,
+        Expr::parse("(d & b) | d")?
+    );
+    assert_eq!(
+        Rewriter::new(rules(), Order::TopDown).rewrite(&expr)?,
+        Expr::parse("c | d")?
+    );
+
+    let cycle = Rewriter::new(
+        vec![Box::new(|expr: &Expr| match expr {
+            Expr::Atom(name) => Some(Expr::Atom(format!("{name}'"))),
+            _ => None,
+        })],
+        Order::BottomUp,
+    );
+    assert!(cycle.rewrite(&expr).is_err());
+
+    Ok(())
+}
+
+// Deep enough to overflow recursive traversals, though not dropping the
+// formula, which recurses regardless
+#[test]
+fn deep_formulas() -> Result<(), String> {
+    log_init();
+
+    let depth = 10_000;
+    let expr = nested(depth);
+    let mut visitor = Depth::default();
+    expr.walk(&mut visitor);
+    assert_eq!(visitor.max, depth + 1);
+    assert_eq!(expr.names(), ["a".to_string()].into());
+
+    let rewriter = Rewriter::new(vec![Box::new(double_negation)], Order::BottomUp);
+    assert_eq!(rewriter.rewrite(&expr)?, Expr::Atom("a".to_string()));
+
+    let rename = |expr: &Expr| match expr {
+        Expr::Atom(name) if name == "a" => Some(Expr::Atom("b".to_string())),
+        _ => None,
+    };
+    let rewriter = Rewriter::new(vec![Box::new(rename)], Order::TopDown);
+    assert_eq!(rewriter.rewrite(&expr)?.names(), ["b".to_string()].into());
+
+    Ok(())
+}
+
+#[test]
+fn parse_pattern() -> Result<(), String> {
+    log_init();
+
+    let pattern = Pattern::parse("?x & (?x | ?y) => ?x")?;
+    assert_eq!(pattern.lhs(), &Expr::parse("?x & (?x | ?y)")?);
+    assert_eq!(pattern.rhs(), &Expr::parse("?x")?);
+    assert_eq!(format!("{pattern:?}"), "(?x | ?y) & ?x => ?x");
+    assert_eq!(
+        Pattern::parse("(?x = ?y) => (?x > ?y) & (?y > ?x)")?.rhs(),
+        &Expr::parse("(?x > ?y) & (?y > ?x)")?
+    );
+
+    assert!(Pattern::parse("?x & ?y").is_err());
+    let err = Pattern::parse("?x | ?y => ?x").unwrap_err();
+    assert_eq!(err, "Unsound rewrite ?x | ?y => ?x");
+    let err = Pattern::parse("?x => ?x & (?x | ?y)").unwrap_err();
+    assert!(err.starts_with("Metavariable ?y"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn match_pattern() -> Result<(), String> {
+    log_init();
+
+    let absorption = Pattern::parse("?x & (?x | ?y) => ?x")?;
+    for (input, expected) in [
+        ("(b > c) & ((b > c) | d)", Some("b > c")),
+        // Commuted, and nested by associativity
+        ("(d | a) & a", Some("a")),
+        ("(c | (b | a)) & a", Some("a")),
+        // Left over operands are kept
+        ("a & (e & (a | b))", Some("e & a")),
+        ("a & (b | c)", None),
+        ("a | (a & b)", None),
+    ] {
+        assert_eq!(
+            absorption.rewrite(&Expr::parse(input)?),
+            expected.map(Expr::parse).transpose()?,
+            "{input}"
+        );
+    }
+
+    // ~?x matches negated atoms of normal forms
+    let double_negation = Pattern::parse("~~?x => ?x")?;
+    assert_eq!(
+        double_negation.rewrite(&Expr::not(Expr::NotAtom("a".to_string()))),
+        Some(Expr::Atom("a".to_string()))
+    );
+
+    // Each metavariable stands for the same formula throughout
+    let idempotence = Pattern::parse("?x ^ ?x => ?x & ~?x")?;
+    assert!(idempotence.rewrite(&Expr::parse("a ^ a")?).is_some());
+    assert!(idempotence.rewrite(&Expr::parse("a ^ b")?).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn rewrite_patterns() -> Result<(), String> {
+    log_init();
+
+    let rules = [
+        "~(?x & ?y) => ~?x | ~?y",
+        "~(?x | ?y) => ~?x & ~?y",
+        "~~?x => ?x",
+        "?x > ?y => ~?x | ?y",
+    ]
+    .into_iter()
+    .map(|input| Pattern::parse(input).map(|pattern| Box::new(pattern) as Box<dyn Rewrite>))
+    .collect::<Result<Vec<_>, _>>()?;
+    let rewriter = Rewriter::new(rules, Order::TopDown);
+    assert_eq!(
+        rewriter.rewrite(&Expr::parse("~((a > b) & ~(c | ~d))")?)?,
+        Expr::parse("(a & ~b) | (c | ~d)")?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use btree_dag::AddVertex;
+use coalescence::{
+    coalesceable::Coalesceable,
+    expression::Expr,
+    parseable::{parse_sequents, Parseable},
+    sequent::Sequent,
+    texable::Texable,
+    Dag, Set,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+#[test]
+fn parse_sequent() -> Result<(), String> {
+    log_init();
+
+    let expected = Sequent::new(
+        &[Expr::parse("a > b")?, Expr::parse("a")?],
+        &[Expr::parse("b")?, Expr::parse("c")?],
+    );
+
+    assert_eq!(Sequent::parse("a > b, a |- b, c")?, expected);
+
+    assert_eq!(Sequent::parse("a > b, a ⊢ b, c")?, expected);
+
+    assert_eq!(Sequent::parse("(a > b), a --> b, c")?, expected);
+
+    assert_eq!(
+        Sequent::parse("|- a | ~a")?,
+        Sequent::new(&[], &[Expr::parse("a | ~a")?])
+    );
+
+    assert_eq!(
+        Sequent::parse("a & ~a -->")?,
+        Sequent::new(&[Expr::parse("a & ~a")?], &[])
+    );
+
+    assert!(Sequent::parse("a & b").is_err());
+
+    assert!(Sequent::parse("a <--> b").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn parse_bidirectional_sequent() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(
+        parse_sequents("a & b <--> b & a")?,
+        vec![
+            Sequent::parse("a & b |- b & a")?,
+            Sequent::parse("b & a |- a & b")?
+        ]
+    );
+
+    assert_eq!(parse_sequents("a |- a")?, vec![Sequent::parse("a |- a")?]);
+
+    Ok(())
+}
+
+#[test]
+fn coalesce_sequent() -> Result<(), String> {
+    log_init();
+
+    let sequent = Sequent::parse("a > b, a |- b")?;
+    let (tokens, _) = sequent.coalesce().ok_or("Not coalesceable")?;
+    assert!(tokens.contains(&Set::from([sequent])));
+
+    let _ = Sequent::parse("a > b, b |- a")?
+        .coalesce()
+        .ok_or("Not coalesceable")
+        .expect_err("False sequent coalesceable");
+
+    Ok(())
+}
+
+#[test]
+fn coalesce_sequent_steps() -> Result<(), String> {
+    log_init();
+
+    // Tokens hold sequents of one formula, short of the sequent itself
+    let sequent = Sequent::parse("a |- a")?;
+    let mut proof = Dag::new();
+    proof.add_vertex(Set::new());
+    let tokens = sequent.spawn(&mut proof);
+    assert_eq!(
+        tokens,
+        Set::from([Set::from([
+            Sequent::parse("|- a")?,
+            Sequent::new(&[], &[Expr::parse("~a")?.normal()])
+        ])])
+    );
+    let tokens = sequent.fire(&mut proof, &tokens);
+    let tokens = sequent.fire(&mut proof, &tokens);
+    assert!(tokens.contains(&Set::from([sequent])), "{tokens:?}");
+
+    Ok(())
+}
+
+#[test]
+fn prove_sequent() -> Result<(), String> {
+    log_init();
+
+    let sequent = Sequent::parse("a |- a")?;
+    let proof = sequent.prove().ok_or("Not provable")?;
+
+    assert_eq!(format!("{sequent:?}"), "a |- a");
+    assert!(proof
+        .to_string()
+        .starts_with("a |- a [⊢]\n  ⊢ a | ~a [|]\n"));
+    assert!(proof
+        .tex()
+        .ends_with("\\RightLabel{$\\mathrm{Seq}$}\n\\UnaryInfC{$a \\vdash a$}\n\\end{prooftree}"));
+
+    Ok(())
+}
+
+#[test]
+fn prove_original_connectives() -> Result<(), String> {
+    log_init();
+
+    let sequent = Sequent::parse("a > b |- ~b > ~a")?;
+    let proof = sequent.prove().ok_or("Not provable")?;
+
+    assert_eq!(format!("{sequent:?}"), "a > b |- ~(b) > ~(a)");
+    assert!(proof.to_string().contains("~(b) > ~(a)"));
+    assert!(proof.to_string().contains("~(a > b)"));
+    assert!(proof.tex().contains("\\neg b \\to \\neg a"));
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "serde")]
+
+use coalescence::{
+    coalesceable::Coalesceable,
+    expression::Expr,
+    parseable::Parseable,
+    proof::{ProofGraph, Rule},
+    Set,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+#[test]
+fn serialize_expr() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("(a & ~b) | ~(c)")?;
+    let json = serde_json::to_string(&expr).map_err(|err| err.to_string())?;
+
+    assert_eq!(
+        json,
+        r#"{"Or":[{"And":[{"Not":{"Atom":"b"}},{"Atom":"a"}]},{"Not":{"Atom":"c"}}]}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<Expr>(&json).map_err(|err| err.to_string())?,
+        expr
+    );
+
+    Ok(())
+}
+
+#[test]
+fn serialize_tokens() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("a > a")?.normal();
+    let (tokens, _) = expr.coalesce().ok_or("Not coalesceable")?;
+    let json = serde_json::to_string(&tokens).map_err(|err| err.to_string())?;
+
+    assert_eq!(
+        serde_json::from_str::<Set<Set<Expr>>>(&json).map_err(|err| err.to_string())?,
+        tokens
+    );
+
+    Ok(())
+}
+
+#[test]
+fn serialize_proof_graph() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("(a > a) & (b > b)")?.normal();
+    let (_, dag) = expr.coalesce().ok_or("Not coalesceable")?;
+    let graph = ProofGraph::from(&dag);
+
+    assert_eq!(graph.nodes[0].token, Set::new());
+    assert_eq!(graph.nodes[0].rule, None);
+    assert!(graph
+        .nodes
+        .iter()
+        .any(|node| node.token == Set::from([expr.clone()])
+            && node.rule == Some(Rule::And(expr.clone()))));
+
+    let json = serde_json::to_string(&graph).map_err(|err| err.to_string())?;
+    let parsed = serde_json::from_str::<ProofGraph>(&json).map_err(|err| err.to_string())?;
+    assert_eq!(parsed, graph);
+    assert_eq!(ProofGraph::from(&parsed.dag()?), graph);
+
+    Ok(())
+}
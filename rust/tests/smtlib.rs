@@ -0,0 +1,108 @@
+use coalescence::{
+    expression::Expr,
+    parseable::Parseable,
+    smtlib::{Script, Status},
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+#[test]
+fn parse_script() -> Result<(), String> {
+    log_init();
+
+    let script = Script::parse(
+        "\
+; Modus ponens, negated
+(set-logic QF_UF)
+(declare-const a Bool)
+(declare-fun b () Bool)
+(assert (=> a b))
+(assert a)
+(assert (not b))
+(check-sat)
+(exit)
+",
+    )?;
+
+    assert_eq!(script.assertions.len(), 3);
+    assert_eq!(script.assertions[0], Expr::parse("a > b")?);
+    assert_eq!(script.check_sat(), Status::Unsat);
+    assert_eq!(script.check_sat().to_string(), "unsat");
+
+    Ok(())
+}
+
+#[test]
+fn parse_operators() -> Result<(), String> {
+    log_init();
+
+    let declarations = "(declare-const a Bool) (declare-const b Bool) (declare-const c Bool)";
+    let check = |assertion: &str| {
+        Script::parse(&format!("{declarations} (assert {assertion}) (check-sat)"))
+            .map(|script| script.check_sat())
+    };
+
+    assert_eq!(check("(and a (not a))")?, Status::Unsat);
+    assert_eq!(check("(or a b)")?, Status::Sat);
+    assert_eq!(
+        check("(not (= (xor a b) (or (and a (not b)) (and (not a) b))))")?,
+        Status::Unsat
+    );
+    assert_eq!(
+        check("(not (= (ite a b c) (or (and a b) (and (not a) c))))")?,
+        Status::Unsat
+    );
+    assert_eq!(check("(not (=> a b a))")?, Status::Unsat);
+    assert_eq!(
+        check("(let ((x (and a b))) (and x (not a)))")?,
+        Status::Unsat
+    );
+    assert_eq!(check("(distinct a b c)")?, Status::Unsat);
+    assert_eq!(check("(and true (not false))")?, Status::Sat);
+
+    // Too many names to search for a model once coalescence falls short
+    let names = (0..20).map(|index| format!("p{index}")).collect::<Vec<_>>();
+    let script = Script::parse(&format!(
+        "{} (assert (or {})) (check-sat)",
+        names
+            .iter()
+            .map(|name| format!("(declare-const {name} Bool)"))
+            .collect::<String>(),
+        names.join(" ")
+    ))?;
+    assert_eq!(script.check_sat(), Status::Unknown);
+    assert_eq!(script.check_sat().to_string(), "unknown");
+
+    assert!(check("d").is_err());
+    assert!(check("(and a").is_err());
+    assert!(Script::parse("(declare-const x Int)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn write_script() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("~(a & b) | c")?;
+    assert_eq!(expr.to_smtlib(), "(or (not (and a b)) c)");
+    assert_eq!(
+        expr.to_smtlib_validity(),
+        "\
+(declare-const a Bool)
+(declare-const b Bool)
+(declare-const c Bool)
+(assert (not (or (not (and a b)) c)))
+(check-sat)
+"
+    );
+
+    let script = Script::parse(&Expr::parse("a > a")?.to_smtlib_validity())?;
+    assert_eq!(script.check_sat(), Status::Unsat);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use coalescence::{
+    coalesceable::Coalesceable, expression::Expr, parseable::Parseable, proof::Proof,
+    texable::Texable, Set,
+};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+#[test]
+fn tex_expr() -> Result<(), String> {
+    log_init();
+
+    assert_eq!(Expr::parse("a")?.tex(), "a");
+
+    assert_eq!(Expr::parse("alpha_1")?.tex(), "\\mathit{alpha\\_1}");
+
+    assert_eq!(
+        Expr::Atom("a\\b^c~d".to_string()).tex(),
+        "\\mathit{a\\textbackslash{}b\\^{}c\\~{}d}"
+    );
+
+    assert_eq!(Expr::parse("~a")?.normal().tex(), "\\neg a");
+
+    assert_eq!(Expr::parse("~(a | b)")?.tex(), "\\neg (a \\lor b)");
+
+    assert_eq!(
+        Expr::parse("(a & b) | ~c")?.normal().tex(),
+        "(a \\land b) \\lor \\neg c"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tex_proof() -> Result<(), String> {
+    log_init();
+
+    let expr = Expr::parse("a > a")?.normal();
+    let (_, dag) = expr.coalesce().ok_or("Not coalesceable")?;
+    let proof = Proof::reconstruct(&expr, &dag).ok_or("Not reconstructable")?;
+
+    assert_eq!(
+        proof.tex(),
+        [
+            "\\begin{prooftree}",
+            "\\AxiomC{$\\vdash a, \\neg a$}",
+            "\\RightLabel{$\\lor$}",
+            "\\UnaryInfC{$\\vdash a \\lor \\neg a, a$}",
+            "\\RightLabel{$\\lor$}",
+            "\\UnaryInfC{$\\vdash a \\lor \\neg a$}",
+            "\\end{prooftree}",
+        ]
+        .join("\n")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tex_wide_proof() -> Result<(), String> {
+    log_init();
+
+    let expr =
+        Expr::parse("(a | ~a) & (b | ~b) & (c | ~c) & (d | ~d) & (e | ~e) & (f | ~f)")?.normal();
+    let (_, dag) = expr.coalesce().ok_or("Not coalesceable")?;
+    let proof = Proof::reconstruct(&expr, &dag).ok_or("Not reconstructable")?;
+
+    let tex = proof.tex();
+    assert!(tex.contains("\\QuinaryInfC"));
+    assert!(!tex.contains("InfC{$\\vdash ((a"));
+    assert!(tex.ends_with(&format!(
+        "\\BinaryInfC{{${}$}}\n\\end{{prooftree}}",
+        Set::from([expr]).tex()
+    )));
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use coalescence::{expression::Expr, parseable::Parseable, theory::Theory};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+fn theory(axioms: &[(&str, &str)]) -> Result<Theory, String> {
+    axioms
+        .iter()
+        .map(|(name, axiom)| Ok((name.to_string(), Expr::parse(axiom)?)))
+        .collect()
+}
+
+#[test]
+fn prove_in_theory() -> Result<(), String> {
+    log_init();
+
+    let theory = theory(&[
+        ("rain", "rain > wet"),
+        ("sprinkler", "sprinkler > wet"),
+        ("raining", "rain"),
+    ])?;
+
+    let proof = theory.prove(&Expr::parse("wet")?).ok_or("Not proven")?;
+    assert_eq!(proof.used, vec!["rain", "raining"]);
+
+    let proof = theory
+        .prove(&Expr::parse("rain | ~rain")?)
+        .ok_or("Not proven")?;
+    assert_eq!(proof.used, Vec::<String>::new());
+
+    assert_eq!(theory.prove(&Expr::parse("sprinkler")?), None);
+
+    Ok(())
+}
+
+#[test]
+fn prove_chained_in_theory() -> Result<(), String> {
+    log_init();
+
+    let theory = theory(&[("ab", "a > b"), ("bc", "b > c"), ("a", "a"), ("d", "d")])?;
+
+    let proof = theory.prove(&Expr::parse("c")?).ok_or("Not proven")?;
+    assert_eq!(proof.used, vec!["a", "ab", "bc"]);
+    assert_eq!(
+        theory.prove(&Expr::parse("a & c")?).map(|proof| proof.used),
+        Some(vec!["a".to_string(), "ab".to_string(), "bc".to_string()])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn prove_in_empty_theory() -> Result<(), String> {
+    log_init();
+
+    let mut theory = Theory::new();
+    assert_eq!(theory.prove(&Expr::parse("a")?), None);
+
+    theory.insert("a", Expr::parse("a")?);
+    let proof = theory.prove(&Expr::parse("a")?).ok_or("Not proven")?;
+    assert_eq!(proof.used, vec!["a"]);
+
+    theory.remove("a");
+    assert!(theory.is_empty());
+
+    Ok(())
+}
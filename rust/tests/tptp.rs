@@ -0,0 +1,113 @@
+use std::{env, fs, path::Path};
+
+use coalescence::{expression::Expr, parseable::Parseable, tptp::Problem};
+
+use pretty_assertions::assert_eq;
+
+fn log_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+#[test]
+fn parse_fof() -> Result<(), String> {
+    log_init();
+
+    let problem = Problem::parse(
+        "\
+% Modus ponens
+fof(rule, axiom, p => q).
+fof(fact, hypothesis, p, file('SYN000+0.p', fact)).
+/* The conjecture */
+fof(goal, conjecture, (q | $false) & ~ ~ q).
+",
+        Path::new("."),
+    )?;
+
+    assert_eq!(problem.theory.axioms.len(), 2);
+    assert_eq!(problem.theory.axioms["rule"], Expr::parse("p > q")?);
+    assert_eq!(problem.conjectures.len(), 1);
+
+    let proof = problem.prove().ok_or("Not proven")?;
+    assert_eq!(proof.used, vec!["fact", "rule"]);
+
+    Ok(())
+}
+
+#[test]
+fn parse_cnf() -> Result<(), String> {
+    log_init();
+
+    // Without a conjecture, the clauses are refuted
+    let problem = Problem::parse(
+        "\
+cnf(c1, axiom, p | q).
+cnf(c2, axiom, ~p).
+cnf(c3, negated_conjecture, ~q).
+",
+        Path::new("."),
+    )?;
+    assert_eq!(problem.goal(), Expr::bottom());
+    assert!(problem.prove().is_some());
+
+    let problem = Problem::parse("cnf(c1, axiom, p | q).\n", Path::new("."))?;
+    assert_eq!(problem.prove(), None);
+
+    Ok(())
+}
+
+#[test]
+fn parse_include() -> Result<(), String> {
+    log_init();
+
+    let directory = env::temp_dir().join(format!("coalescence-tptp-{}", std::process::id()));
+    fs::create_dir_all(directory.join("Axioms")).map_err(|err| err.to_string())?;
+    fs::write(
+        directory.join("Axioms/SYN000+0.ax"),
+        "fof(a1, axiom, p <=> q).\nfof(a2, axiom, r).\n",
+    )
+    .map_err(|err| err.to_string())?;
+    fs::write(
+        directory.join("SYN000+1.p"),
+        "include('Axioms/SYN000+0.ax', [a1]).\nfof(c, conjecture, q <= p).\n",
+    )
+    .map_err(|err| err.to_string())?;
+
+    let problem = Problem::load(&directory.join("SYN000+1.p"), &directory)?;
+    assert_eq!(problem.theory.axioms.keys().collect::<Vec<_>>(), vec!["a1"]);
+    assert_eq!(problem.prove().ok_or("Not proven")?.used, vec!["a1"]);
+
+    let _ = fs::remove_dir_all(&directory);
+    Ok(())
+}
+
+#[test]
+fn parse_first_order() -> Result<(), String> {
+    log_init();
+
+    assert!(Problem::parse("fof(a, axiom, p(a)).", Path::new(".")).is_err());
+    assert!(Problem::parse("fof(a, axiom, ![X]: p).", Path::new(".")).is_err());
+    assert!(Problem::parse("fof(a, axiom, X).", Path::new(".")).is_err());
+    assert!(Problem::parse("fof(a, type, p).", Path::new(".")).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn parse_duplicate_names() -> Result<(), String> {
+    log_init();
+
+    let err = Problem::parse("fof(a, axiom, p).\nfof(a, axiom, q).\n", Path::new(".")).unwrap_err();
+    assert_eq!(err, "Duplicate formula name a");
+    assert!(Problem::parse("fof(a, axiom, p).\nfof(a, conjecture, p).", Path::new(".")).is_err());
+
+    let directory = env::temp_dir().join(format!("coalescence-tptp-dup-{}", std::process::id()));
+    fs::create_dir_all(&directory).map_err(|err| err.to_string())?;
+    fs::write(directory.join("SYN000+0.ax"), "fof(a1, axiom, p).\n")
+        .map_err(|err| err.to_string())?;
+    let problem = Problem::parse("include('SYN000+0.ax').\nfof(a1, axiom, q).\n", &directory);
+    let err = problem.unwrap_err();
+    assert!(err.contains("Duplicate formula name a1"), "{err}");
+
+    let _ = fs::remove_dir_all(&directory);
+    Ok(())
+}